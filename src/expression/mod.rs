@@ -12,7 +12,14 @@
 
 //#[macro_use]
 extern crate term;
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::fmt;
+use std::mem;
+use std::str::FromStr;
+
+// The width of isize and usize in the function being verified
+thread_local!(static POINTER_WIDTH: Cell<u8> = Cell::new(64));
 
 // Boolean Expression type
 #[derive(Clone, PartialEq)]
@@ -28,7 +35,27 @@ pub enum Expression {
     BooleanLiteral(bool),
     // Integer literals
     UnsignedBitVector(UnsignedBitVectorData),
-    SignedBitVector(SignedBitVectorData)
+    SignedBitVector(SignedBitVectorData),
+    // Integer literals whose type has not been inferred yet
+    IntegerLiteral(String),
+    // isize and usize literals, whose size is not known until the pointer width is
+    PointerSizedLiteral(String, Types),
+    // A universally or existentially quantified sub-expression
+    Quantifier(QuantifierData),
+    // One of two sub-expressions, depending on a condition
+    Conditional(ConditionalData),
+    // A sub-expression with a variable bound to a value
+    Let(LetData),
+    // A call to a built-in specification function
+    FunctionCall(FunctionCallData),
+    // A sub-expression converted to another type, as with Rust's "as"
+    Cast(CastData),
+    // 128-bit integer literals that do not fit in 64 bits
+    WideBitVector(WideBitVectorData),
+    // Character literals
+    CharLiteral(char),
+    // Floating-point literals
+    FloatLiteral(FloatLiteralData)
 }
 
 // Used for representing Expression types as strings, recursively.
@@ -46,7 +73,42 @@ impl fmt::Display for Expression {
             },
             Expression::SignedBitVector(ref s) => {
                 write!(f, "({}i{})", s.value, s.size.to_string())
-            }
+            },
+            Expression::IntegerLiteral(ref i) => write!(f, "({})", i),
+            Expression::PointerSizedLiteral(ref i, ref t) => write!(f, "({}{})", i, t),
+            Expression::Quantifier(ref q) => {
+                match (q.lower.as_ref(), q.upper.as_ref()) {
+                    (Some(l), Some(u)) => {
+                        write!(f, "({} {} in {}..{}, {})", q.quantifier, q.var, **l, **u, *q.body)
+                    },
+                    _ => write!(f, "({} {}, {})", q.quantifier, q.var, *q.body),
+                }
+            },
+            Expression::Conditional(ref c) => {
+                write!(f, "(if {} then {} else {})", *c.condition, *c.then_expression,
+                       *c.else_expression)
+            },
+            Expression::Let(ref l) => write!(f, "(let {} = {} in {})", l.var, *l.value, *l.body),
+            Expression::FunctionCall(ref c) => write!(f, "{}", c),
+            Expression::Cast(ref c) => write!(f, "({} as {})", *c.e, c.ty),
+            Expression::WideBitVector(ref w) => {
+                if w.signed && w.high >> 63 == 1 {
+                    let (high, low) = negate_u128(w.high, w.low);
+                    write!(f, "(-{}i128)", u128_to_string(high, low))
+                } else if w.signed {
+                    write!(f, "({}i128)", u128_to_string(w.high, w.low))
+                } else {
+                    write!(f, "({}u128)", u128_to_string(w.high, w.low))
+                }
+            },
+            Expression::CharLiteral(ref c) => write!(f, "({:?})", c),
+            Expression::FloatLiteral(ref l) => {
+                if l.size == 32 {
+                    write!(f, "({:?}f32)", unsafe { mem::transmute::<u32, f32>(l.bits as u32) })
+                } else {
+                    write!(f, "({:?}f64)", unsafe { mem::transmute::<u64, f64>(l.bits) })
+                }
+            },
         }
     }
 }
@@ -90,6 +152,75 @@ impl fmt::Display for VariableMappingData {
     }
 }
 
+// A quantified boolean expression. If a range is given, the bound variable ranges over
+// lower..upper (lower inclusive, upper exclusive), like a Rust range.
+#[derive(Clone, PartialEq)]
+pub struct QuantifierData {
+    pub quantifier: Quantifier,
+    pub var: VariableMappingData,
+    pub lower: Option<Box<Expression>>,
+    pub upper: Option<Box<Expression>>,
+    pub body: Box<Expression>
+}
+
+#[derive(Clone, PartialEq)]
+pub struct ConditionalData {
+    pub condition: Box<Expression>,
+    pub then_expression: Box<Expression>,
+    pub else_expression: Box<Expression>
+}
+
+#[derive(Clone, PartialEq)]
+pub struct CastData {
+    pub e: Box<Expression>,
+    pub ty: Types
+}
+
+#[derive(Clone, PartialEq)]
+pub struct LetData {
+    pub var: VariableMappingData,
+    pub value: Box<Expression>,
+    pub body: Box<Expression>
+}
+
+#[derive(Clone, PartialEq)]
+pub struct FunctionCallData {
+    pub name: String,
+    pub args: Vec<Expression>,
+    // The parameter types of a user-defined predicate, or None for a built-in function
+    pub params: Option<Vec<Types>>
+}
+
+// A function marked #[predicate], which may be called from a condition
+#[derive(Clone, PartialEq)]
+pub struct Predicate {
+    pub params: Vec<VariableMappingData>,
+    // An expression over the parameters, equivalent to the function returning true
+    pub body: Expression
+}
+
+impl fmt::Display for FunctionCallData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let args: Vec<String> = self.args.iter().map(|a| a.to_string()).collect();
+        write!(f, "{}({})", self.name, args.join(", "))
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Quantifier {
+    Forall,
+    Exists,
+}
+
+impl fmt::Display for Quantifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Quantifier::Forall => { write!(f, "forall") },
+            Quantifier::Exists => { write!(f, "exists") },
+        }
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct UnsignedBitVectorData {
     pub size: u8,
@@ -102,6 +233,22 @@ pub struct SignedBitVectorData {
     pub value: i64,
 }
 
+// A floating-point literal of size 32 or 64, stored as its IEEE 754 bits
+#[derive(Clone, PartialEq)]
+pub struct FloatLiteralData {
+    pub size: u8,
+    pub bits: u64,
+}
+
+// A 128-bit literal, stored as its high and low 64 bits in two's complement. Literals that fit in
+// 64 bits are stored as UnsignedBitVectorData or SignedBitVectorData of size 128 instead.
+#[derive(Clone, PartialEq)]
+pub struct WideBitVectorData {
+    pub signed: bool,
+    pub high: u64,
+    pub low: u64,
+}
+
 #[derive(Clone, PartialEq)]
 pub enum BinaryOperator {
     // Normal operators
@@ -178,6 +325,8 @@ pub enum UnaryOperator {
     BitwiseNot,
     // Boolean logical operator
     Not,
+    // The value of an expression on entry to the function
+    Old,
 }
 
 impl fmt::Display for UnaryOperator {
@@ -185,7 +334,8 @@ impl fmt::Display for UnaryOperator {
         match *self {
             UnaryOperator::Negation => { write!(f, "-") },
             UnaryOperator::BitwiseNot => { write!(f, "!") },
-            UnaryOperator::Not => { write!(f, "NOT") }
+            UnaryOperator::Not => { write!(f, "NOT") },
+            UnaryOperator::Old => { write!(f, "old") },
         }
     }
 }
@@ -201,7 +351,17 @@ pub enum Types {
 	U16,
 	U32,
 	U64,
-    Void
+    I128,
+    U128,
+    Isize,
+    Usize,
+    // A Unicode scalar value, as a 32-bit bit-vector
+    Char,
+    F32,
+    F64,
+    Void,
+    // A variable whose type has not been inferred yet
+    Unknown
 }
 
 impl fmt::Display for Types {
@@ -216,7 +376,15 @@ impl fmt::Display for Types {
             Types::U16 => { write!(f, "u16") },
             Types::U32 => { write!(f, "u32") },
             Types::U64 => { write!(f, "u64") },
+            Types::I128 => { write!(f, "i128") },
+            Types::U128 => { write!(f, "u128") },
+            Types::Isize => { write!(f, "isize") },
+            Types::Usize => { write!(f, "usize") },
+            Types::Char => { write!(f, "char") },
+            Types::F32 => { write!(f, "f32") },
+            Types::F64 => { write!(f, "f64") },
             Types::Void => { write!(f, "()") },
+            Types::Unknown => { write!(f, "_") },
         }
     }
 }
@@ -229,20 +397,53 @@ impl fmt::Display for Types {
 /// * `replacement` - The Expression to replace the target, if found.
 ///
 /// # Remarks:
+/// * A quantifier or let whose bound variable has the target's name shadows it. A bound variable
+///   that would capture a variable of the replacement is renamed first.
+/// * Variables within `old()` are replaced as well. Use `substitute_state_variable()` for a change
+///   to the program state, which `old()` does not see.
 ///
 pub fn substitute_variable_with_expression (source_expression: &mut Expression,
                                             target: &VariableMappingData,
                                             replacement: &Expression) {
+    substitute(source_expression, target, replacement, false);
+}
+
+/// Recurses through an Expression and replaces any instance of a variable with a given Expression,
+/// except within `old()`.
+///
+/// # Arguments:
+/// * `source_expression` - The Expression to be recursed through.
+/// * `target` - The variable to be replaced.
+/// * `replacement` - The Expression to replace the target, if found.
+///
+/// # Remarks:
+/// * Used for assignments while generating the weakest precondition. `old()` refers to the state
+///   on entry to the function, so the variables within it are left for `remove_old()`.
+///
+pub fn substitute_state_variable (source_expression: &mut Expression,
+                                  target: &VariableMappingData,
+                                  replacement: &Expression) {
+    substitute(source_expression, target, replacement, true);
+}
+
+// Replaces any instance of a variable with a given Expression, skipping old() if skip_old is set.
+// Bound variables are always renamed within old(), so that they still match their binder.
+fn substitute (source_expression: &mut Expression,
+               target: &VariableMappingData,
+               replacement: &Expression,
+               skip_old: bool) {
     let mut replace: bool = false;
     match source_expression {
         &mut Expression::BinaryExpression(ref mut b) => {
             // Recurisvely call the sub-expressions
-            substitute_variable_with_expression(&mut(*b.left), target, replacement);
-            substitute_variable_with_expression(&mut(*b.right), target, replacement);
+            substitute(&mut(*b.left), target, replacement, skip_old);
+            substitute(&mut(*b.right), target, replacement, skip_old);
         },
         &mut Expression::UnaryExpression(ref mut u) => {
-            // Recurisvely call the sub-expression
-            substitute_variable_with_expression(&mut(*u.e), target, replacement);
+            // Recurisvely call the sub-expression, unless it refers to the entry state
+            if !(skip_old && u.op == UnaryOperator::Old) {
+                substitute(&mut(*u.e), target, replacement, skip_old);
+            }
         },
         &mut Expression::VariableMapping(ref mut v) => {
             // Substitute the variable if it matches the target
@@ -250,6 +451,60 @@ pub fn substitute_variable_with_expression (source_expression: &mut Expression,
                 replace = true;
             }
         },
+        &mut Expression::Quantifier(ref mut q) => {
+            // The range is outside the scope of the bound variable
+            if let Some(ref mut l) = q.lower {
+                substitute(&mut(**l), target, replacement, skip_old);
+            }
+            if let Some(ref mut u) = q.upper {
+                substitute(&mut(**u), target, replacement, skip_old);
+            }
+            // The body is only substituted if the bound variable does not shadow the target
+            if q.var.name != target.name {
+                if occurs_free(&q.var.name, replacement) {
+                    let renamed = VariableMappingData {
+                        name: fresh_name(&q.var.name, replacement, &*q.body),
+                        var_type: q.var.var_type.clone()
+                    };
+                    substitute_variable_with_expression(&mut(*q.body),
+                                                        &q.var,
+                                                        &Expression::VariableMapping(renamed.clone()));
+                    q.var = renamed;
+                }
+                substitute(&mut(*q.body), target, replacement, skip_old);
+            }
+        },
+        &mut Expression::Conditional(ref mut c) => {
+            substitute(&mut(*c.condition), target, replacement, skip_old);
+            substitute(&mut(*c.then_expression), target, replacement, skip_old);
+            substitute(&mut(*c.else_expression), target, replacement, skip_old);
+        },
+        &mut Expression::Let(ref mut l) => {
+            // The value is outside the scope of the bound variable
+            substitute(&mut(*l.value), target, replacement, skip_old);
+            // The body is only substituted if the bound variable does not shadow the target
+            if l.var.name != target.name {
+                if occurs_free(&l.var.name, replacement) {
+                    let renamed = VariableMappingData {
+                        name: fresh_name(&l.var.name, replacement, &*l.body),
+                        var_type: l.var.var_type.clone()
+                    };
+                    substitute_variable_with_expression(&mut(*l.body),
+                                                        &l.var,
+                                                        &Expression::VariableMapping(renamed.clone()));
+                    l.var = renamed;
+                }
+                substitute(&mut(*l.body), target, replacement, skip_old);
+            }
+        },
+        &mut Expression::FunctionCall(ref mut c) => {
+            for arg in c.args.iter_mut() {
+                substitute(arg, target, replacement, skip_old);
+            }
+        },
+        &mut Expression::Cast(ref mut c) => {
+            substitute(&mut(*c.e), target, replacement, skip_old);
+        },
         _ => {
             // No substitution should be done
         }
@@ -261,6 +516,207 @@ pub fn substitute_variable_with_expression (source_expression: &mut Expression,
     }
 }
 
+/// Checks if a variable with the given name occurs free (outside the scope of a quantifier
+/// binding the same name) in an Expression.
+///
+/// # Arguments:
+/// * `name` - The name of the variable.
+/// * `expression` - The Expression to be recursed through.
+///
+/// # Return:
+/// * `true` if it does, `false` otherwise
+///
+pub fn occurs_free (name: &str, expression: &Expression) -> bool {
+    match *expression {
+        Expression::BinaryExpression(ref b) => {
+            occurs_free(name, &*b.left) || occurs_free(name, &*b.right)
+        },
+        Expression::UnaryExpression(ref u) => occurs_free(name, &*u.e),
+        Expression::VariableMapping(ref v) => v.name == name,
+        Expression::Quantifier(ref q) => {
+            q.lower.as_ref().map_or(false, |l| occurs_free(name, &**l))
+            || q.upper.as_ref().map_or(false, |u| occurs_free(name, &**u))
+            || (q.var.name != name && occurs_free(name, &*q.body))
+        },
+        Expression::Conditional(ref c) => {
+            occurs_free(name, &*c.condition)
+            || occurs_free(name, &*c.then_expression)
+            || occurs_free(name, &*c.else_expression)
+        },
+        Expression::Let(ref l) => {
+            occurs_free(name, &*l.value) || (l.var.name != name && occurs_free(name, &*l.body))
+        },
+        Expression::FunctionCall(ref c) => c.args.iter().any(|a| occurs_free(name, a)),
+        Expression::Cast(ref c) => occurs_free(name, &*c.e),
+        _ => false,
+    }
+}
+
+/// Collects the variables that occur free in an Expression.
+///
+/// # Arguments:
+/// * `expression` - The Expression to be recursed through.
+///
+/// # Return:
+/// * The free variables, each listed once, in the order they first occur
+///
+pub fn free_variables (expression: &Expression) -> Vec<VariableMappingData> {
+    let mut vars: Vec<VariableMappingData> = Vec::new();
+    collect_free_variables(expression, &mut Vec::new(), &mut vars);
+    vars
+}
+
+// Adds the variables that occur free in an Expression, and are not bound by the enclosing
+// binders, to vars
+fn collect_free_variables(expression: &Expression,
+                          bound: &mut Vec<String>,
+                          vars: &mut Vec<VariableMappingData>) {
+    match *expression {
+        Expression::BinaryExpression(ref b) => {
+            collect_free_variables(&*b.left, bound, vars);
+            collect_free_variables(&*b.right, bound, vars);
+        },
+        Expression::UnaryExpression(ref u) => collect_free_variables(&*u.e, bound, vars),
+        Expression::VariableMapping(ref v) => {
+            if !bound.contains(&v.name) && !vars.contains(v) {
+                vars.push(v.clone());
+            }
+        },
+        Expression::Quantifier(ref q) => {
+            if let Some(ref l) = q.lower {
+                collect_free_variables(&**l, bound, vars);
+            }
+            if let Some(ref u) = q.upper {
+                collect_free_variables(&**u, bound, vars);
+            }
+            bound.push(q.var.name.clone());
+            collect_free_variables(&*q.body, bound, vars);
+            bound.pop();
+        },
+        Expression::Conditional(ref c) => {
+            collect_free_variables(&*c.condition, bound, vars);
+            collect_free_variables(&*c.then_expression, bound, vars);
+            collect_free_variables(&*c.else_expression, bound, vars);
+        },
+        Expression::Let(ref l) => {
+            collect_free_variables(&*l.value, bound, vars);
+            bound.push(l.var.name.clone());
+            collect_free_variables(&*l.body, bound, vars);
+            bound.pop();
+        },
+        Expression::FunctionCall(ref c) => {
+            for arg in c.args.iter() {
+                collect_free_variables(arg, bound, vars);
+            }
+        },
+        Expression::Cast(ref c) => collect_free_variables(&*c.e, bound, vars),
+        _ => {},
+    }
+}
+
+// Returns a variant of a variable name that occurs free in neither of the given Expressions.
+fn fresh_name(name: &str, first: &Expression, second: &Expression) -> String {
+    let mut n = 1;
+    loop {
+        let candidate = format!("{}!{}", name, n);
+        if !occurs_free(&candidate, first) && !occurs_free(&candidate, second) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// Recurses through an Expression and replaces every `old(e)` with `e`.
+///
+/// # Arguments:
+/// * `expression` - The Expression to be recursed through.
+///
+/// # Remarks:
+/// * Only valid once the Expression describes the state on entry to the function, i.e. on the
+///   weakest precondition of the whole function body.
+///
+pub fn remove_old (expression: &mut Expression) {
+    let inner: Expression;
+    match *expression {
+        Expression::BinaryExpression(ref mut b) => {
+            remove_old(&mut *b.left);
+            remove_old(&mut *b.right);
+            return;
+        },
+        Expression::UnaryExpression(ref mut u) => {
+            remove_old(&mut *u.e);
+            if u.op != UnaryOperator::Old {
+                return;
+            }
+            inner = (*u.e).clone();
+        },
+        Expression::Quantifier(ref mut q) => {
+            if let Some(ref mut l) = q.lower {
+                remove_old(&mut **l);
+            }
+            if let Some(ref mut u) = q.upper {
+                remove_old(&mut **u);
+            }
+            remove_old(&mut *q.body);
+            return;
+        },
+        Expression::Conditional(ref mut c) => {
+            remove_old(&mut *c.condition);
+            remove_old(&mut *c.then_expression);
+            remove_old(&mut *c.else_expression);
+            return;
+        },
+        Expression::Let(ref mut l) => {
+            remove_old(&mut *l.value);
+            remove_old(&mut *l.body);
+            return;
+        },
+        Expression::FunctionCall(ref mut c) => {
+            for arg in c.args.iter_mut() {
+                remove_old(arg);
+            }
+            return;
+        },
+        Expression::Cast(ref mut c) => {
+            remove_old(&mut *c.e);
+            return;
+        },
+        _ => return,
+    }
+
+    // Replace old(e) after the match to avoid scope issues
+    *expression = inner;
+}
+
+/// Checks if an Expression contains `old(e)` anywhere within it.
+///
+/// # Arguments:
+/// * `expression` - The Expression to be recursed through.
+///
+/// # Return:
+/// * `true` if it does, `false` otherwise
+///
+pub fn contains_old (expression: &Expression) -> bool {
+    match *expression {
+        Expression::BinaryExpression(ref b) => contains_old(&*b.left) || contains_old(&*b.right),
+        Expression::UnaryExpression(ref u) => u.op == UnaryOperator::Old || contains_old(&*u.e),
+        Expression::Quantifier(ref q) => {
+            q.lower.as_ref().map_or(false, |l| contains_old(&**l))
+            || q.upper.as_ref().map_or(false, |u| contains_old(&**u))
+            || contains_old(&*q.body)
+        },
+        Expression::Conditional(ref c) => {
+            contains_old(&*c.condition)
+            || contains_old(&*c.then_expression)
+            || contains_old(&*c.else_expression)
+        },
+        Expression::Let(ref l) => contains_old(&*l.value) || contains_old(&*l.body),
+        Expression::FunctionCall(ref c) => c.args.iter().any(|a| contains_old(a)),
+        Expression::Cast(ref c) => contains_old(&*c.e),
+        _ => false,
+    }
+}
+
 /// Recurses through an Expression and returns the type it would evaluate to.
 ///
 /// # Arguments:
@@ -306,11 +762,12 @@ pub fn determine_evaluation_type ( expression: &Expression ) -> Types {
                 Expression::UnaryExpression(ref u) => {
                     match u.op {
                         UnaryOperator::Negation
-                        | UnaryOperator::Not => determine_evaluation_type(&*u.e),
+                        | UnaryOperator::Not
+                        | UnaryOperator::Old => determine_evaluation_type(&*u.e),
                         UnaryOperator::BitwiseNot => determine_evaluation_type(&*u.e),
                     }
                 },
-                Expression::VariableMapping(ref v) => v.var_type.clone(),
+                Expression::VariableMapping(ref v) => concrete_type(v.var_type.clone()),
                 Expression::BooleanLiteral(_) => Types::Bool,
                 Expression::UnsignedBitVector(ref u) => {
                     match u.size {
@@ -318,6 +775,7 @@ pub fn determine_evaluation_type ( expression: &Expression ) -> Types {
                         16 => Types::U16,
                         32 => Types::U32,
                         64 => Types::U64,
+                        128 => Types::U128,
                         _ => {
                             rp_error!(
                                 "Invalid or Unsupported integer type: \"u{}\"",
@@ -332,6 +790,7 @@ pub fn determine_evaluation_type ( expression: &Expression ) -> Types {
                         16 => Types::I16,
                         32 => Types::I32,
                         64 => Types::I64,
+                        128 => Types::I128,
                         _ => {
                             rp_error!(
                                 "Invalid or Unsupported integer type: \"i{}\"",
@@ -339,13 +798,80 @@ pub fn determine_evaluation_type ( expression: &Expression ) -> Types {
                             );
                         }
                     }
-                }
+                },
+                // Rejected by ty_check()
+                Expression::IntegerLiteral(_) | Expression::PointerSizedLiteral(..) => {
+                    unreachable!()
+                },
+                Expression::Quantifier(_) => Types::Bool,
+                Expression::Conditional(ref c) => determine_evaluation_type(&*c.then_expression),
+                Expression::Let(ref l) => determine_evaluation_type(&*l.body),
+                Expression::FunctionCall(ref c) => {
+                    match function_call_type(c) {
+                        Ok(t) => t,
+                        Err(e) => rp_error!("{}", e),
+                    }
+                },
+                Expression::Cast(ref c) => concrete_type(c.ty.clone()),
+                Expression::WideBitVector(ref w) => {
+                    if w.signed { Types::I128 } else { Types::U128 }
+                },
+                Expression::CharLiteral(_) => Types::Char,
+                Expression::FloatLiteral(ref l) => {
+                    if l.size == 32 { Types::F32 } else { Types::F64 }
+                },
             }
         },
         Err(e) => rp_error!("{}", e),
     }
 }
 
+/// Joins a list of boolean Expressions with AND.
+///
+/// # Arguments:
+/// * `expressions` - The Expressions to be joined.
+///
+/// # Return:
+/// * The conjunction of `expressions`, or `true` if it is empty.
+///
+pub fn conjunction(expressions: Vec<Expression>) -> Expression {
+    let mut iter = expressions.into_iter();
+    let first = match iter.next() {
+        Some(e) => e,
+        None => return Expression::BooleanLiteral(true),
+    };
+    iter.fold(first, |left, right| {
+        Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::And,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    })
+}
+
+/// Joins a list of boolean Expressions with OR.
+///
+/// # Arguments:
+/// * `expressions` - The Expressions to be joined.
+///
+/// # Return:
+/// * The disjunction of `expressions`, or `false` if it is empty.
+///
+pub fn disjunction(expressions: Vec<Expression>) -> Expression {
+    let mut iter = expressions.into_iter();
+    let first = match iter.next() {
+        Some(e) => e,
+        None => return Expression::BooleanLiteral(false),
+    };
+    iter.fold(first, |left, right| {
+        Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::Or,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    })
+}
+
 /// Recurses through an Expression and checks for validity of types, operands, and integer bounds.
 ///
 /// # Arguments:
@@ -384,6 +910,27 @@ pub fn ty_check( expression: &Expression ) -> Result<bool, String> {
                                                 b.op
                                             )
                                         )
+                                    // Characters have no arithmetic
+                                    } else if (l_type == Types::Char) || (r_type == Types::Char) {
+                                        Err(
+                                            format!(
+                                                "Invalid use of binary operator {} on char \
+                                                value(s)",
+                                                b.op
+                                            )
+                                        )
+                                    // The remainder of floats is not IEEE 754's, which the
+                                    // solver provides
+                                    } else if b.op == BinaryOperator::Modulo
+                                              && (is_float_type(l_type.clone())
+                                                  || is_float_type(r_type.clone())) {
+                                        Err(
+                                            format!(
+                                                "Unsupported use of binary operator {} on \
+                                                floating-point value(s)",
+                                                b.op
+                                            )
+                                        )
                                     // Ensure both operand types match
                                     } else if l_type != r_type {
                                         Err(
@@ -420,20 +967,18 @@ pub fn ty_check( expression: &Expression ) -> Result<bool, String> {
                                                 b.op
                                             )
                                         )
-                                    //Ensure both operand types are of same signedness
-                                    } else if (is_valid_signed(&*b.left)
-                                    			&& !is_valid_signed(&*b.right))
-                                            || (is_valid_unsigned(&*b.left)
-                                            	&& !is_valid_unsigned(&*b.right)) {
+                                    } else if (l_type == Types::Char) || (r_type == Types::Char)
+                                              || is_float_type(l_type.clone())
+                                              || is_float_type(r_type.clone()) {
                                         Err(
                                             format!(
-                                                "Binary operand types do not match: {} {} {}",
-                                                l_type,
-                                                b.op,
-                                                r_type
+                                                "Invalid use of binary operator {} on non-integer \
+                                                value(s)",
+                                                b.op
                                             )
                                         )
                                     } else {
+                                        // As in Rust, the shift amount may have any integer type
                                         Ok(true)
                                     }
                                 },
@@ -462,6 +1007,14 @@ pub fn ty_check( expression: &Expression ) -> Result<bool, String> {
                                                 r_type
                                             )
                                         )
+                                    } else if is_float_type(l_type.clone()) {
+                                        Err(
+                                            format!(
+                                                "Invalid use of binary operator {} on \
+                                                floating-point value(s)",
+                                                b.op
+                                            )
+                                        )
                                     } else {
                                         Ok(true)
                                     }
@@ -593,7 +1146,7 @@ pub fn ty_check( expression: &Expression ) -> Result<bool, String> {
                                     )
                                 )
                             // Ensure operand is not an unsigned type
-                            } else if is_valid_unsigned(&*u.e) {
+                            } else if is_valid_unsigned(&*u.e) || e_type == Types::Char {
                                 Err(
                                     format!(
                                         "Invalid use of operator {} on unsigned value {}",
@@ -609,6 +1162,21 @@ pub fn ty_check( expression: &Expression ) -> Result<bool, String> {
                     }
                 },
                 UnaryOperator::BitwiseNot => {
+                    try!(ty_check(&*u.e));
+                    let e_type: Types = determine_evaluation_type(&*u.e);
+                    if is_float_type(e_type) {
+                        Err(
+                            format!(
+                                "Invalid use of operator {} on floating-point value {}",
+                                u.op,
+                                *u.e
+                            )
+                        )
+                    } else {
+                        Ok(true)
+                    }
+                },
+                UnaryOperator::Old => {
                     match ty_check(&*u.e) {
                         Ok(_) => Ok(true),
                         Err(e) => Err(e)
@@ -632,10 +1200,10 @@ pub fn ty_check( expression: &Expression ) -> Result<bool, String> {
             }
         },
         Expression::VariableMapping(ref v) => {
-            if let Types::Void = v.var_type {
-                Err(format!("Variable {} has void type!", v.name))
-            } else {
-                Ok(true)
+            match v.var_type {
+                Types::Void => Err(format!("Variable {} has void type!", v.name)),
+                Types::Unknown => Err(format!("Unable to infer the type of variable {}", v.name)),
+                _ => Ok(true),
             }
         },
         Expression::BooleanLiteral(_) => {
@@ -674,6 +1242,8 @@ pub fn ty_check( expression: &Expression ) -> Result<bool, String> {
                         Err(format!("Out of range value for u64 type: {}", u.value.to_string()))
                     }
                 },
+                // Every 64-bit value is in range
+                128 => Ok(true),
                 _ => {
                    Err(format!("Invalid or unsupported integer type: \"u{}\"", u.size.to_string()))
                 }
@@ -712,6 +1282,8 @@ pub fn ty_check( expression: &Expression ) -> Result<bool, String> {
                         Err(format!("Out of range value for i64 type: {}", s.value.to_string()))
                     }
                 },
+                // Every 64-bit value is in range
+                128 => Ok(true),
                 _ => {
                     Err(
                         format!(
@@ -721,10 +1293,558 @@ pub fn ty_check( expression: &Expression ) -> Result<bool, String> {
                     )
                 }
             }
+        },
+        Expression::IntegerLiteral(ref i) => {
+            Err(format!("Unable to infer the type of integer literal {}", i))
+        },
+        Expression::PointerSizedLiteral(ref i, ref t) => {
+            Err(format!("Unable to infer the size of {} literal {}", t, i))
+        },
+        Expression::WideBitVector(_) | Expression::CharLiteral(_) | Expression::FloatLiteral(_) => {
+            Ok(true)
+        },
+        Expression::Quantifier(ref q) => {
+            match q.var.var_type {
+                Types::Void | Types::Unknown => {
+                    return Err(format!("Unable to infer the type of bound variable {}", q.var.name));
+                },
+                _ => {},
+            }
+            // Ensure the range bounds have the type of the bound variable
+            for bound in q.lower.iter().chain(q.upper.iter()) {
+                try!(ty_check(&**bound));
+                let b_type: Types = determine_evaluation_type(&**bound);
+                if b_type != concrete_type(q.var.var_type.clone()) || b_type == Types::Bool {
+                    return Err(
+                        format!(
+                            "Invalid range bound {} for bound variable {}",
+                            **bound,
+                            q.var
+                        )
+                    );
+                }
+            }
+            // Ensure the body is a boolean type
+            try!(ty_check(&*q.body));
+            if determine_evaluation_type(&*q.body) != Types::Bool {
+                Err(format!("Invalid use of {} on non-boolean value {}", q.quantifier, *q.body))
+            } else {
+                Ok(true)
+            }
+        },
+        Expression::Conditional(ref c) => {
+            try!(ty_check(&*c.condition));
+            try!(ty_check(&*c.then_expression));
+            try!(ty_check(&*c.else_expression));
+            let t_type: Types = determine_evaluation_type(&*c.then_expression);
+            let e_type: Types = determine_evaluation_type(&*c.else_expression);
+            // Ensure the condition is a boolean type
+            if determine_evaluation_type(&*c.condition) != Types::Bool {
+                Err(format!("Invalid use of non-boolean value {} as a condition", *c.condition))
+            // Ensure both branch types match
+            } else if t_type != e_type {
+                Err(format!("Conditional branch types do not match: {} else {}", t_type, e_type))
+            } else {
+                Ok(true)
+            }
+        },
+        Expression::Let(ref l) => {
+            try!(ty_check(&*l.value));
+            try!(ty_check(&*l.body));
+            let v_type: Types = determine_evaluation_type(&*l.value);
+            // Ensure the value matches the type of the bound variable
+            if v_type != concrete_type(l.var.var_type.clone()) {
+                Err(format!("Invalid value {} for bound variable {}", *l.value, l.var))
+            } else {
+                Ok(true)
+            }
+        },
+        Expression::FunctionCall(ref c) => {
+            for arg in c.args.iter() {
+                try!(ty_check(arg));
+            }
+            try!(function_call_type(c));
+            Ok(true)
+        },
+        Expression::Cast(ref c) => {
+            try!(ty_check(&*c.e));
+            let e_type: Types = determine_evaluation_type(&*c.e);
+            // As in Rust, integers, booleans and characters may be cast to integers, only u8
+            // values and characters to characters, and only integers and floats to floats
+            if is_float_type(c.ty.clone()) {
+                if is_float_type(e_type.clone())
+                   || (e_type != Types::Char && type_size(e_type.clone()).is_some()) {
+                    Ok(true)
+                } else {
+                    Err(format!("Invalid cast of {} value {} to {}", e_type, *c.e, c.ty))
+                }
+            } else if type_size(c.ty.clone()).is_none() {
+                Err(format!("Invalid cast to non-integer type {}", c.ty))
+            } else if is_float_type(e_type.clone()) {
+                // Out of the range of the target, the result is undefined in Rust
+                Err(format!("Unsupported cast of floating-point value {} to {}", *c.e, c.ty))
+            } else if e_type != Types::Bool && type_size(e_type.clone()).is_none() {
+                Err(format!("Invalid cast of {} value {}", e_type, *c.e))
+            } else if c.ty == Types::Char && e_type != Types::U8 && e_type != Types::Char {
+                Err(format!("Invalid cast of {} value {} to char", e_type, *c.e))
+            } else {
+                Ok(true)
+            }
         }
     }
 }
 
+/// Checks the arguments of a call to a built-in specification function or predicate.
+///
+/// # Arguments:
+/// * `call` - The call whose arguments have already been type checked.
+///
+/// # Return:
+/// * Ok(Types) with the type of the call's result, if the call is valid.
+/// * Err(String) otherwise.
+///
+/// # Remarks:
+/// * `abs` takes a signed integer; `min` and `max` take two integers of the same type. All three
+///   return the type of their arguments.
+/// * `pow` takes an integer and a constant `u32` exponent, and returns the type of the integer.
+/// * `count_ones`, `leading_zeros`, and `trailing_zeros` take any integer and return a `u32`.
+/// * `is_power_of_two` takes an unsigned integer and returns a `bool`.
+/// * `abs` and `pow` use wrapping bit-vector semantics, unlike the Rust methods in debug builds,
+///   which panic on overflow.
+/// * A predicate takes arguments of its parameter types and returns a `bool`.
+///
+pub fn function_call_type(call: &FunctionCallData) -> Result<Types, String> {
+    try!(check_arity(call));
+    if let Some(ref params) = call.params {
+        for (arg, param) in call.args.iter().zip(params.iter()) {
+            if determine_evaluation_type(arg) != *param {
+                return Err(format!("Invalid argument {} for predicate {}: expected {}",
+                                   arg, call.name, param));
+            }
+        }
+        return Ok(Types::Bool);
+    }
+    let a_type: Types = determine_evaluation_type(&call.args[0]);
+    let valid = match call.name.as_str() {
+        "abs" => is_signed_type(a_type.clone()),
+        "min" | "max" => {
+            (is_signed_type(a_type.clone()) || is_unsigned_type(a_type.clone()))
+            && determine_evaluation_type(&call.args[1]) == a_type
+        },
+        "pow" => {
+            match call.args[1] {
+                Expression::UnsignedBitVector(ref u) if u.size == 32 => {},
+                _ => {
+                    return Err(
+                        format!("Invalid exponent {}: it must be a constant u32", call.args[1])
+                    );
+                },
+            }
+            is_signed_type(a_type.clone()) || is_unsigned_type(a_type.clone())
+        },
+        "is_power_of_two" => is_unsigned_type(a_type.clone()),
+        "is_nan" | "is_finite" | "is_infinite" => is_float_type(a_type.clone()),
+        _ => is_signed_type(a_type.clone()) || is_unsigned_type(a_type.clone()),
+    };
+    if !valid {
+        return Err(format!("Invalid argument types for {}", call));
+    }
+    match call.name.as_str() {
+        "count_ones" | "leading_zeros" | "trailing_zeros" => Ok(Types::U32),
+        "is_power_of_two" | "is_nan" | "is_finite" | "is_infinite" => Ok(Types::Bool),
+        _ => Ok(a_type),
+    }
+}
+
+/// Checks whether a name is that of a built-in specification function.
+///
+/// # Arguments:
+/// * `name` - The name of a function.
+///
+/// # Return:
+/// * `true` if a call to `name` refers to a built-in function, `false` otherwise.
+///
+pub fn is_builtin_function(name: &str) -> bool {
+    match name {
+        "abs" | "min" | "max" | "pow" | "count_ones" | "leading_zeros" | "trailing_zeros"
+        | "is_power_of_two" | "is_nan" | "is_finite" | "is_infinite" => true,
+        _ => false,
+    }
+}
+
+/// Records the signatures of the predicates called in an Expression.
+///
+/// # Arguments:
+/// * `expression` - An Expression whose calls will be resolved in place.
+/// * `predicates` - The predicates that may be called, by name.
+///
+/// # Remarks:
+/// * Calls to other names are left for `ty_check()` to check against the built-in functions.
+///
+pub fn resolve_predicates(expression: &mut Expression, predicates: &HashMap<String, Predicate>) {
+    match *expression {
+        Expression::BinaryExpression(ref mut b) => {
+            resolve_predicates(&mut *b.left, predicates);
+            resolve_predicates(&mut *b.right, predicates);
+        },
+        Expression::UnaryExpression(ref mut u) => resolve_predicates(&mut *u.e, predicates),
+        Expression::Quantifier(ref mut q) => {
+            if let Some(ref mut l) = q.lower {
+                resolve_predicates(&mut **l, predicates);
+            }
+            if let Some(ref mut u) = q.upper {
+                resolve_predicates(&mut **u, predicates);
+            }
+            resolve_predicates(&mut *q.body, predicates);
+        },
+        Expression::Conditional(ref mut c) => {
+            resolve_predicates(&mut *c.condition, predicates);
+            resolve_predicates(&mut *c.then_expression, predicates);
+            resolve_predicates(&mut *c.else_expression, predicates);
+        },
+        Expression::Let(ref mut l) => {
+            resolve_predicates(&mut *l.value, predicates);
+            resolve_predicates(&mut *l.body, predicates);
+        },
+        Expression::FunctionCall(ref mut c) => {
+            for arg in c.args.iter_mut() {
+                resolve_predicates(arg, predicates);
+            }
+            if let Some(p) = predicates.get(&c.name) {
+                c.params = Some(p.params.iter().map(|v| v.var_type.clone()).collect());
+            }
+        },
+        Expression::Cast(ref mut c) => resolve_predicates(&mut *c.e, predicates),
+        _ => {},
+    }
+}
+
+// Checks that a call names a built-in function or predicate and passes it enough arguments
+fn check_arity(call: &FunctionCallData) -> Result<(), String> {
+    let arity: usize = match call.name.as_str() {
+        _ if call.params.is_some() => call.params.as_ref().unwrap().len(),
+        "abs" | "count_ones" | "leading_zeros" | "trailing_zeros" | "is_power_of_two"
+        | "is_nan" | "is_finite" | "is_infinite" => 1,
+        "min" | "max" | "pow" => 2,
+        _ => return Err(format!("Unknown function {}", call.name)),
+    };
+    if call.args.len() != arity {
+        return Err(
+            format!(
+                "Function {} takes {} argument(s) but {} were given",
+                call.name,
+                arity,
+                call.args.len()
+            )
+        );
+    }
+    Ok(())
+}
+
+/// Fills in the types of untyped variables and integer literals in an Expression.
+///
+/// # Arguments:
+/// * `expression` - An Expression whose missing types will be inferred in place.
+/// * `env` - The variables the Expression may refer to, with their declared types.
+///
+/// # Return:
+/// * Ok(()) if every variable and literal now has a type.
+/// * Err(String) otherwise, the String containing a message about the first problem encountered.
+///
+/// # Remarks:
+/// * Variable types come from `env`. An explicit annotation must agree with `env`.
+/// * Literal types come from the operand they are combined with, or from the expected type of
+///   the enclosing operator. Mismatched operand types are left for `ty_check()` to report.
+///
+pub fn infer_types(expression: &mut Expression, env: &[VariableMappingData])
+                   -> Result<(), String> {
+    match infer(expression, env, Some(Types::Bool)) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+// Infers the types within an Expression, given the type its parent expects it to have (if known).
+// Returns the type of the Expression, or None if it consists only of untyped literals.
+fn infer(expression: &mut Expression, env: &[VariableMappingData], expected: Option<Types>)
+         -> Result<Option<Types>, String> {
+    let literal: Expression;
+    let literal_type: Option<Types>;
+    match *expression {
+        Expression::BinaryExpression(ref mut b) => {
+            match b.op {
+                // Both operands and the result share a type
+                BinaryOperator::Addition
+                | BinaryOperator::Subtraction
+                | BinaryOperator::Multiplication
+                | BinaryOperator::Division
+                | BinaryOperator::Modulo
+                | BinaryOperator::BitwiseOr
+                | BinaryOperator::BitwiseAnd
+                | BinaryOperator::BitwiseXor => {
+                    return infer_operands(&mut *b.left, &mut *b.right, env, expected);
+                },
+                // The result shares the type of the left operand; the shift amount may differ
+                BinaryOperator::BitwiseLeftShift | BinaryOperator::BitwiseRightShift => {
+                    let l_type = try!(infer(&mut *b.left, env, expected));
+                    if try!(infer(&mut *b.right, env, None)).is_none() {
+                        try!(infer(&mut *b.right, env, l_type.clone()));
+                    }
+                    return Ok(l_type);
+                },
+                // Both operands share a type, and the result is a boolean
+                BinaryOperator::LessThan
+                | BinaryOperator::LessThanOrEqual
+                | BinaryOperator::GreaterThan
+                | BinaryOperator::GreaterThanOrEqual
+                | BinaryOperator::Equal
+                | BinaryOperator::NotEqual
+                | BinaryOperator::SignedMultiplicationDoesNotOverflow
+                | BinaryOperator::SignedMultiplicationDoesNotUnderflow
+                | BinaryOperator::UnsignedMultiplicationDoesNotOverflow => {
+                    match try!(infer_operands(&mut *b.left, &mut *b.right, env, None)) {
+                        Some(_) => return Ok(Some(Types::Bool)),
+                        None => {
+                            return Err(
+                                format!(
+                                    "Unable to infer the operand types of {}: both are untyped \
+                                    literals",
+                                    b.op
+                                )
+                            );
+                        },
+                    }
+                },
+                // Both operands are booleans
+                BinaryOperator::And
+                | BinaryOperator::Or
+                | BinaryOperator::Xor
+                | BinaryOperator::Implication
+                | BinaryOperator::BiImplication => {
+                    try!(infer(&mut *b.left, env, Some(Types::Bool)));
+                    try!(infer(&mut *b.right, env, Some(Types::Bool)));
+                    return Ok(Some(Types::Bool));
+                },
+            }
+        },
+        Expression::UnaryExpression(ref mut u) => {
+            match u.op {
+                UnaryOperator::Negation | UnaryOperator::BitwiseNot | UnaryOperator::Old => {
+                    return infer(&mut *u.e, env, expected);
+                },
+                UnaryOperator::Not => {
+                    try!(infer(&mut *u.e, env, Some(Types::Bool)));
+                    return Ok(Some(Types::Bool));
+                },
+            }
+        },
+        Expression::VariableMapping(ref mut v) => {
+            let declared = env.iter().find(|d| d.name == v.name).map(|d| d.var_type.clone());
+            match declared {
+                Some(t) => {
+                    if v.var_type == Types::Unknown {
+                        v.var_type = t;
+                    } else if v.var_type != t {
+                        return Err(
+                            format!(
+                                "Variable {} is declared as {} but annotated as {}",
+                                v.name,
+                                t,
+                                v.var_type
+                            )
+                        );
+                    }
+                },
+                None => {
+                    if v.var_type == Types::Unknown {
+                        return Err(format!("Unknown variable {} in condition", v.name));
+                    }
+                },
+            }
+            return Ok(Some(v.var_type.clone()));
+        },
+        Expression::BooleanLiteral(_) => return Ok(Some(Types::Bool)),
+        Expression::UnsignedBitVector(_)
+        | Expression::SignedBitVector(_)
+        | Expression::WideBitVector(_)
+        | Expression::CharLiteral(_)
+        | Expression::FloatLiteral(_) => {
+            return Ok(Some(determine_evaluation_type(expression)));
+        },
+        Expression::IntegerLiteral(ref i) => {
+            match expected {
+                Some(ref t) => literal = try!(typed_integer_literal(i, t)),
+                None => return Ok(None),
+            }
+            literal_type = expected.clone();
+        },
+        // The size of an isize or usize literal is that of the pointer width being verified
+        Expression::PointerSizedLiteral(ref i, ref t) => {
+            literal = try!(typed_integer_literal(i, t));
+            literal_type = Some(t.clone());
+        },
+        Expression::Quantifier(ref mut q) => {
+            // An untyped bound variable takes the type of its range
+            if let (Some(l), Some(u)) = (q.lower.as_mut(), q.upper.as_mut()) {
+                let bound = if q.var.var_type == Types::Unknown {
+                    None
+                } else {
+                    Some(q.var.var_type.clone())
+                };
+                if let Some(t) = try!(infer_operands(&mut **l, &mut **u, env, bound)) {
+                    if q.var.var_type == Types::Unknown {
+                        q.var.var_type = t;
+                    }
+                }
+            }
+            if q.var.var_type == Types::Unknown {
+                return Err(format!("Unable to infer the type of bound variable {}", q.var.name));
+            }
+            // The bound variable shadows any variable of the same name
+            let mut scoped = vec![q.var.clone()];
+            scoped.extend_from_slice(env);
+            try!(infer(&mut *q.body, &scoped, Some(Types::Bool)));
+            return Ok(Some(Types::Bool));
+        },
+        Expression::Conditional(ref mut c) => {
+            try!(infer(&mut *c.condition, env, Some(Types::Bool)));
+            return infer_operands(&mut *c.then_expression, &mut *c.else_expression, env, expected);
+        },
+        Expression::Let(ref mut l) => {
+            // An untyped bound variable takes the type of its value
+            let annotated = if l.var.var_type == Types::Unknown {
+                None
+            } else {
+                Some(l.var.var_type.clone())
+            };
+            match try!(infer(&mut *l.value, env, annotated)) {
+                Some(t) => l.var.var_type = t,
+                None => {
+                    return Err(format!("Unable to infer the type of bound variable {}", l.var.name));
+                },
+            }
+            // The bound variable shadows any variable of the same name
+            let mut scoped = vec![l.var.clone()];
+            scoped.extend_from_slice(env);
+            return infer(&mut *l.body, &scoped, expected);
+        },
+        Expression::FunctionCall(ref mut c) => {
+            try!(check_arity(c));
+            if let Some(ref params) = c.params {
+                for (arg, param) in c.args.iter_mut().zip(params.iter()) {
+                    try!(infer(arg, env, Some(param.clone())));
+                }
+                return Ok(Some(Types::Bool));
+            }
+            match c.name.as_str() {
+                "abs" => return infer(&mut c.args[0], env, expected),
+                "min" | "max" => {
+                    let (first, rest) = c.args.split_at_mut(1);
+                    return infer_operands(&mut first[0], &mut rest[0], env, expected);
+                },
+                "pow" => {
+                    try!(infer(&mut c.args[1], env, Some(Types::U32)));
+                    return infer(&mut c.args[0], env, expected);
+                },
+                "count_ones" | "leading_zeros" | "trailing_zeros" => {
+                    try!(infer(&mut c.args[0], env, None));
+                    return Ok(Some(Types::U32));
+                },
+                "is_power_of_two" | "is_nan" | "is_finite" | "is_infinite" => {
+                    try!(infer(&mut c.args[0], env, None));
+                    return Ok(Some(Types::Bool));
+                },
+                _ => unreachable!(),
+            }
+        },
+        Expression::Cast(ref mut c) => {
+            // An untyped literal takes the target type, so "5 as u8" is a u8 literal. Only u8
+            // values may be cast to char.
+            if try!(infer(&mut *c.e, env, None)).is_none() {
+                match c.ty {
+                    Types::Bool | Types::F32 | Types::F64 => {
+                        return Err(format!("Unable to infer the type of {}", *c.e));
+                    },
+                    Types::Char => try!(infer(&mut *c.e, env, Some(Types::U8))),
+                    _ => try!(infer(&mut *c.e, env, Some(c.ty.clone()))),
+                };
+            }
+            return Ok(Some(c.ty.clone()));
+        },
+    }
+
+    // Replace the untyped literal after the match to avoid scope issues
+    *expression = literal;
+    Ok(literal_type)
+}
+
+// Infers the types of two operands that must share a type. If only one of them has a known type,
+// the other is inferred from it.
+fn infer_operands(left: &mut Expression,
+                  right: &mut Expression,
+                  env: &[VariableMappingData],
+                  expected: Option<Types>)
+                  -> Result<Option<Types>, String> {
+    let l_type = try!(infer(left, env, expected.clone()));
+    let r_type = try!(infer(right, env, l_type.clone().or(expected)));
+    if l_type.is_none() && r_type.is_some() {
+        try!(infer(left, env, r_type.clone()));
+    }
+    Ok(l_type.or(r_type))
+}
+
+/// Creates a bit-vector literal of the given type from the text of an integer literal.
+///
+/// # Arguments:
+/// * `value` - The decimal text of the literal, possibly negative.
+/// * `ty` - The integer type of the literal.
+///
+/// # Return:
+/// * Ok(Expression) if `value` is in range for `ty`.
+/// * Err(String) otherwise.
+///
+pub fn typed_integer_literal(value: &str, ty: &Types) -> Result<Expression, String> {
+    let literal = match *ty {
+        Types::I8 => i8::from_str(value).ok().map(|i| {
+            Expression::SignedBitVector( SignedBitVectorData { size: 8, value: i as i64 } )
+        }),
+        Types::I16 => i16::from_str(value).ok().map(|i| {
+            Expression::SignedBitVector( SignedBitVectorData { size: 16, value: i as i64 } )
+        }),
+        Types::I32 => i32::from_str(value).ok().map(|i| {
+            Expression::SignedBitVector( SignedBitVectorData { size: 32, value: i as i64 } )
+        }),
+        Types::I64 => i64::from_str(value).ok().map(|i| {
+            Expression::SignedBitVector( SignedBitVectorData { size: 64, value: i as i64 } )
+        }),
+        Types::U8 => u8::from_str(value).ok().map(|u| {
+            Expression::UnsignedBitVector( UnsignedBitVectorData { size: 8, value: u as u64 } )
+        }),
+        Types::U16 => u16::from_str(value).ok().map(|u| {
+            Expression::UnsignedBitVector( UnsignedBitVectorData { size: 16, value: u as u64 } )
+        }),
+        Types::U32 => u32::from_str(value).ok().map(|u| {
+            Expression::UnsignedBitVector( UnsignedBitVectorData { size: 32, value: u as u64 } )
+        }),
+        Types::U64 => u64::from_str(value).ok().map(|u| {
+            Expression::UnsignedBitVector( UnsignedBitVectorData { size: 64, value: u as u64 } )
+        }),
+        Types::I128 => wide_integer_literal(value, true),
+        Types::U128 => wide_integer_literal(value, false),
+        Types::Isize | Types::Usize => {
+            return typed_integer_literal(value, &concrete_type(ty.clone())).map_err(|_| {
+                format!("Out of range value for {}-bit {} type: {}", pointer_width(), ty, value)
+            });
+        },
+        _ => return Err(format!("Integer literal {} used where {} was expected", value, ty)),
+    };
+
+    match literal {
+        Some(l) => Ok(l),
+        None => Err(format!("Out of range value for {} type: {}", ty, value)),
+    }
+}
+
 /// Checks if an Expression matches one of the supported unsigned integer types
 ///
 /// # Arguments:
@@ -734,7 +1854,7 @@ pub fn ty_check( expression: &Expression ) -> Result<bool, String> {
 /// * `true` if it matches, `false` otherwise
 ///
 /// # Remarks:
-/// * Current supported types: u8, u16, u32, u64
+/// * Current supported types: u8, u16, u32, u64, usize
 ///
 pub fn is_valid_unsigned(e: &Expression) -> bool {
     match *e {
@@ -760,7 +1880,7 @@ pub fn is_valid_unsigned(e: &Expression) -> bool {
 /// * `true` if it matches, `false` otherwise
 ///
 /// # Remarks:
-/// * Current supported types: i8, i16, i32, i64
+/// * Current supported types: i8, i16, i32, i64, isize
 ///
 pub fn is_valid_signed(e: &Expression) -> bool {
     match *e {
@@ -787,11 +1907,11 @@ pub fn is_valid_signed(e: &Expression) -> bool {
 /// * `true` if it matches, `false` otherwise
 ///
 /// # Remarks:
-/// * Current supported types: u8, u16, u32, u64
+/// * Current supported types: u8, u16, u32, u64, usize
 ///
 pub fn is_unsigned_type(t: Types) -> bool {
 	match t {
-		Types::U8 | Types::U16 | Types::U32 | Types::U64 => true,
+		Types::U8 | Types::U16 | Types::U32 | Types::U64 | Types::U128 | Types::Usize => true,
 		_ => false,
 	}
 }
@@ -806,15 +1926,341 @@ pub fn is_unsigned_type(t: Types) -> bool {
 /// * `true` if it matches, `false` otherwise
 ///
 /// # Remarks:
-/// * Current supported types: i8, i16, i32, i64
+/// * Current supported types: i8, i16, i32, i64, isize
 ///
 pub fn is_signed_type(t: Types) -> bool {
 	match t {
-		Types::I8 | Types::I16 | Types::I32 | Types::I64 => true,
+		Types::I8 | Types::I16 | Types::I32 | Types::I64 | Types::I128 | Types::Isize => true,
 		_ => false,
 	}
 }
 
+/// Checks if a Types is one of the floating-point types
+///
+/// # Arguments:
+/// * `t` - A Types
+///
+/// # Return:
+/// * `true` if it is `f32` or `f64`, `false` otherwise
+///
+pub fn is_float_type(t: Types) -> bool {
+    match t {
+        Types::F32 | Types::F64 => true,
+        _ => false,
+    }
+}
+
+/// Creates a floating-point literal of the given type from the text of a Rust float literal.
+///
+/// # Arguments:
+/// * `value` - The decimal text of the literal, e.g. "-1.5".
+/// * `ty` - `f32` or `f64`.
+///
+/// # Return:
+/// * Ok(Expression) holding the bits of the nearest value of `ty`, as Rust rounds it.
+/// * Err(String) if `value` is not a float literal.
+///
+pub fn float_literal(value: &str, ty: &Types) -> Result<Expression, String> {
+    let literal = match *ty {
+        Types::F32 => f32::from_str(value).ok().map(|f| {
+            FloatLiteralData { size: 32, bits: unsafe { mem::transmute::<f32, u32>(f) } as u64 }
+        }),
+        Types::F64 => f64::from_str(value).ok().map(|f| {
+            FloatLiteralData { size: 64, bits: unsafe { mem::transmute::<f64, u64>(f) } }
+        }),
+        _ => return Err(format!("Float literal {} used where {} was expected", value, ty)),
+    };
+    match literal {
+        Some(l) => Ok(Expression::FloatLiteral(l)),
+        None => Err(format!("Invalid {} literal: {}", ty, value)),
+    }
+}
+
+/// Creates a character literal from the hexadecimal digits of a `\u{...}` escape.
+///
+/// # Arguments:
+/// * `hex` - The digits between the braces of the escape, e.g. "1F600".
+///
+/// # Return:
+/// * Ok(Expression) if `hex` is the code point of a Unicode scalar value.
+/// * Err(String) otherwise, as for a surrogate or a value above 10FFFF.
+///
+pub fn escaped_char_literal(hex: &str) -> Result<Expression, String> {
+    match u32::from_str_radix(hex, 16).ok().and_then(::std::char::from_u32) {
+        Some(c) => Ok(Expression::CharLiteral(c)),
+        None => Err(format!("Invalid character escape: \\u{{{}}}", hex)),
+    }
+}
+
+/// Returns the number of bits in an integer type
+///
+/// # Arguments:
+/// * `t` - A Types
+///
+/// # Return:
+/// * The width of the type in bits, or `None` if it is not an integer type
+///
+pub fn type_size(t: Types) -> Option<u8> {
+	match t {
+		Types::I8 | Types::U8 => Some(8),
+		Types::I16 | Types::U16 => Some(16),
+		Types::I32 | Types::U32 => Some(32),
+		Types::I64 | Types::U64 => Some(64),
+		Types::I128 | Types::U128 => Some(128),
+		Types::Char => Some(32),
+		Types::Isize | Types::Usize => Some(pointer_width()),
+		_ => None,
+	}
+}
+
+/// Sets the width of `isize` and `usize`.
+///
+/// # Arguments:
+/// * `width` - The pointer width of the target the function is verified for: 16, 32 or 64.
+///
+/// # Remarks:
+/// * Set before the conditions of a function are parsed, as the range of `usize` literals and the
+///   value of `usize::MAX` depend on it.
+///
+pub fn set_pointer_width(width: u8) {
+    POINTER_WIDTH.with(|w| w.set(width));
+}
+
+/// Returns the width of `isize` and `usize`, as set by `set_pointer_width()`.
+pub fn pointer_width() -> u8 {
+    POINTER_WIDTH.with(|w| w.get())
+}
+
+/// Returns the fixed-width integer type that a type has on the current target.
+///
+/// # Arguments:
+/// * `t` - A Types
+///
+/// # Return:
+/// * The signed or unsigned type of the pointer width if `t` is `isize` or `usize`, `t` otherwise.
+///
+/// # Remarks:
+/// * Expressions evaluate to fixed-width types, so a `usize` value may be compared with a `u64`
+///   value on a 64-bit target, as they are the same bit-vectors.
+///
+pub fn concrete_type(t: Types) -> Types {
+    match (t, pointer_width()) {
+        (Types::Isize, 16) => Types::I16,
+        (Types::Isize, 32) => Types::I32,
+        (Types::Isize, _) => Types::I64,
+        (Types::Usize, 16) => Types::U16,
+        (Types::Usize, 32) => Types::U32,
+        (Types::Usize, _) => Types::U64,
+        (t, _) => t,
+    }
+}
+
+/// Returns the smallest value of an integer type.
+///
+/// # Arguments:
+/// * `ty` - An integer type
+///
+/// # Return:
+/// * A bit-vector literal of the smallest value of `ty`.
+///
+pub fn min_value(ty: &Types) -> Expression {
+    let size = type_size(ty.clone()).unwrap();
+    if is_signed_type(ty.clone()) {
+        signed_min(size)
+    } else {
+        Expression::UnsignedBitVector( UnsignedBitVectorData {
+            size: size,
+            value: 0u64,
+        })
+    }
+}
+
+/// Returns the largest value of an integer type.
+///
+/// # Arguments:
+/// * `ty` - An integer type
+///
+/// # Return:
+/// * A bit-vector literal of the largest value of `ty`.
+///
+pub fn max_value(ty: &Types) -> Expression {
+    let size = type_size(ty.clone()).unwrap();
+    if size == 128 {
+        let signed = is_signed_type(ty.clone());
+        return Expression::WideBitVector( WideBitVectorData {
+            signed: signed,
+            high: if signed { !0u64 >> 1 } else { !0u64 },
+            low: !0u64,
+        });
+    }
+    if is_signed_type(ty.clone()) {
+        Expression::SignedBitVector( SignedBitVectorData {
+            size: size,
+            value: !(-1i64 << (size - 1)),
+        })
+    } else {
+        Expression::UnsignedBitVector( UnsignedBitVectorData {
+            size: size,
+            value: !0u64 >> (64 - size),
+        })
+    }
+}
+
+/// Returns the smallest value of a signed bit-vector.
+///
+/// # Arguments:
+/// * `size` - The size of the bit-vector: 8, 16, 32, 64 or 128
+///
+/// # Return:
+/// * A bit-vector literal of the smallest value.
+///
+pub fn signed_min(size: u8) -> Expression {
+    if size == 128 {
+        Expression::WideBitVector( WideBitVectorData {
+            signed: true,
+            high: 1u64 << 63,
+            low: 0u64,
+        })
+    } else {
+        Expression::SignedBitVector( SignedBitVectorData {
+            size: size,
+            value: -1i64 << (size - 1),
+        })
+    }
+}
+
+/// Returns an Expression that is true when a character holds a Unicode scalar value.
+///
+/// # Arguments:
+/// * `e` - An Expression of type char
+///
+/// # Return:
+/// * `e as u32 <= 0x10FFFF && (e as u32 < 0xD800 || e as u32 > 0xDFFF)`
+///
+/// # Remarks:
+/// * A char is a 32-bit bit-vector, so every char argument is assumed to satisfy this.
+///
+pub fn valid_char(e: &Expression) -> Expression {
+    let code = |value: u64| -> Expression {
+        Expression::UnsignedBitVector( UnsignedBitVectorData { size: 32, value: value } )
+    };
+    let compare = |op: BinaryOperator, value: u64| -> Expression {
+        Expression::BinaryExpression( BinaryExpressionData {
+            op: op,
+            left: Box::new(Expression::Cast( CastData {
+                e: Box::new(e.clone()),
+                ty: Types::U32
+            })),
+            right: Box::new(code(value))
+        })
+    };
+    Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::And,
+        left: Box::new(compare(BinaryOperator::LessThanOrEqual, 0x10FFFF)),
+        right: Box::new(Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::Or,
+            left: Box::new(compare(BinaryOperator::LessThan, 0xD800)),
+            right: Box::new(compare(BinaryOperator::GreaterThan, 0xDFFF))
+        }))
+    })
+}
+
+// Creates a 128-bit literal from the decimal text of an integer, if it is in range
+fn wide_integer_literal(value: &str, signed: bool) -> Option<Expression> {
+    let negative = value.starts_with('-');
+    let (high, low) = match parse_u128(if negative { &value[1..] } else { value }) {
+        Some(v) => v,
+        None => return None,
+    };
+    if !signed {
+        if negative {
+            return None;
+        } else if high == 0 {
+            return Some(Expression::UnsignedBitVector( UnsignedBitVectorData {
+                size: 128,
+                value: low
+            }));
+        }
+        return Some(Expression::WideBitVector( WideBitVectorData {
+            signed: false,
+            high: high,
+            low: low
+        }));
+    }
+    // The magnitude may be 2^127 only if the value is negative
+    if high > 1u64 << 63 || (high == 1u64 << 63 && (low != 0 || !negative)) {
+        return None;
+    }
+    if high == 0 && negative && low <= 1u64 << 63 {
+        return Some(Expression::SignedBitVector( SignedBitVectorData {
+            size: 128,
+            value: (low as i64).wrapping_neg()
+        }));
+    } else if high == 0 && !negative && low <= i64::max_value() as u64 {
+        return Some(Expression::SignedBitVector( SignedBitVectorData {
+            size: 128,
+            value: low as i64
+        }));
+    }
+    let (high, low) = if negative { negate_u128(high, low) } else { (high, low) };
+    Some(Expression::WideBitVector( WideBitVectorData {
+        signed: true,
+        high: high,
+        low: low
+    }))
+}
+
+// Parses the decimal text of a non-negative integer less than 2^128, returning its high and low
+// 64 bits
+fn parse_u128(s: &str) -> Option<(u64, u64)> {
+    if s.is_empty() {
+        return None;
+    }
+    let (mut high, mut low) = (0u64, 0u64);
+    for c in s.chars() {
+        let digit = match c.to_digit(10) {
+            Some(d) => d as u64,
+            None => return None,
+        };
+        // (high, low) * 10 + digit, with the low bits multiplied 32 bits at a time
+        let lo = (low & 0xFFFF_FFFF) * 10 + digit;
+        let hi = (low >> 32) * 10 + (lo >> 32);
+        low = (hi << 32) | (lo & 0xFFFF_FFFF);
+        high = match high.checked_mul(10).and_then(|h| h.checked_add(hi >> 32)) {
+            Some(h) => h,
+            None => return None,
+        };
+    }
+    Some((high, low))
+}
+
+// Returns the decimal text of the unsigned 128-bit integer with the given high and low 64 bits
+fn u128_to_string(high: u64, low: u64) -> String {
+    if high == 0 {
+        return low.to_string();
+    }
+    // Long division by 10, 32 bits at a time
+    let mut limbs = [high >> 32, high & 0xFFFF_FFFF, low >> 32, low & 0xFFFF_FFFF];
+    let mut digits: Vec<char> = Vec::new();
+    while limbs.iter().any(|&l| l != 0) {
+        let mut remainder = 0u64;
+        for limb in limbs.iter_mut() {
+            let current = (remainder << 32) | *limb;
+            *limb = current / 10;
+            remainder = current % 10;
+        }
+        digits.push((b'0' + remainder as u8) as char);
+    }
+    digits.iter().rev().cloned().collect()
+}
+
+// Returns the two's complement negation of the 128-bit integer with the given high and low bits
+fn negate_u128(high: u64, low: u64) -> (u64, u64) {
+    let low = (!low).wrapping_add(1);
+    let high = if low == 0 { (!high).wrapping_add(1) } else { !high };
+    (high, low)
+}
+
 /// Returns a `Types` interpreted from a `String` representing a variable or literal type
 ///
 /// # Arguments:
@@ -824,19 +2270,65 @@ pub fn is_signed_type(t: Types) -> bool {
 /// * A Types
 ///
 /// # Remarks:
-/// * Current supported types: bool, i8, i16, i32, i64, u8, u16, u32, u64
+/// * Current supported types: bool, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize
 pub fn string_to_type(s: String) -> Types {
-	match s.as_str() {
-        "bool" => Types::Bool,
-        "i8" => Types::I8,
-        "i16" => Types::I16,
-        "i32" => Types::I32,
-        "i64" => Types::I64,
-        "u8" => Types::U8,
-        "u16" => Types::U16,
-        "u32" => Types::U32,
-        "u64" => Types::U64,
-        "()" => Types::Void,
-        _ => unimplemented!(),
+    match try_string_to_type(s.as_str()) {
+        Some(t) => t,
+        None => rp_unsupported!("Unsupported type {}", s),
+    }
+}
+
+/// Returns a `Types` interpreted from a string representing a variable or literal type, if it is
+/// a supported type
+///
+/// # Arguments:
+/// * `s` - A string
+///
+/// # Return:
+/// * Some(Types) if the type is supported, None otherwise
+///
+/// # Remarks:
+/// * Current supported types: bool, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize
+/// * `Wrapping<T>` has the type `T`
+pub fn try_string_to_type(s: &str) -> Option<Types> {
+    if let Some(t) = wrapped_type(s) {
+        return try_string_to_type(t);
+    }
+	match s {
+        "bool" => Some(Types::Bool),
+        "i8" => Some(Types::I8),
+        "i16" => Some(Types::I16),
+        "i32" => Some(Types::I32),
+        "i64" => Some(Types::I64),
+        "u8" => Some(Types::U8),
+        "u16" => Some(Types::U16),
+        "u32" => Some(Types::U32),
+        "u64" => Some(Types::U64),
+        "i128" => Some(Types::I128),
+        "u128" => Some(Types::U128),
+        "isize" => Some(Types::Isize),
+        "usize" => Some(Types::Usize),
+        "char" => Some(Types::Char),
+        "f32" => Some(Types::F32),
+        "f64" => Some(Types::F64),
+        "()" => Some(Types::Void),
+        _ => None,
 	}
-}
\ No newline at end of file
+}
+
+/// Returns the type wrapped by a `Wrapping<T>`, given the text of the type.
+///
+/// # Arguments:
+/// * `s` - A string representing a type
+///
+/// # Return:
+/// * Some(T) if `s` is `Wrapping<T>`, None otherwise
+///
+pub fn wrapped_type(s: &str) -> Option<&str> {
+    for prefix in ["std::num::Wrapping<", "core::num::Wrapping<"].iter() {
+        if s.starts_with(*prefix) && s.ends_with(">") {
+            return Some(&s[prefix.len()..s.len() - 1]);
+        }
+    }
+    None
+}
@@ -33,6 +33,7 @@
 // The following line is being weird to me sometimes
 #[macro_use] extern crate log;
 extern crate petgraph;
+extern crate lalrpop_util;
 extern crate rustc;
 extern crate rustc_plugin;
 extern crate rustc_data_structures;
@@ -41,20 +42,26 @@ extern crate rustc_const_math;
 extern crate rustc_errors;
 
 // External imports
+use std::any::Any;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
 use rustc_data_structures::indexed_vec::Idx;
 use rustc_plugin::Registry;
 use rustc::mir::repr::{Mir, BasicBlock, BasicBlockData, Arg, Temp, Var, ArgDecl, TempDecl, VarDecl};
-use rustc::mir::transform::{Pass, MirPass, MirSource};
-use rustc::ty::{TyCtxt, FnOutput};
+use rustc::mir::mir_map::MirMap;
+use rustc::mir::transform::{Pass, MirMapPass, MirPassHook, MirSource};
+use rustc::ty::{Ty, TyCtxt, FnOutput, TypeVariants};
+use syntax::attr;
 use syntax::feature_gate::AttributeType;
-use syntax::parse::token::InternedString;
-use syntax::ast::MetaItemKind;
+use syntax::ast::{MetaItemKind, NodeId};
 
 // Local imports
 use expression::*;
 use parser::*;
 use smt_output::*;
 use weakest_precondition::*;
+use reporting::{SKIP_UNSUPPORTED, Unsupported};
 
 // rustproof modules
 mod expression;
@@ -70,18 +77,47 @@ mod tests;
 pub fn registrar(reg: &mut Registry) {
     // If debug is an argument, set the debug flag to true
     let mut debug = false;
+    // If check_all is an argument, check functions without a specification as well
+    let mut check_all = false;
+    // If print_invariants is an argument, print the loop invariants that are inferred
+    let mut print_invariants = false;
+    // The arithmetic semantics of functions without an arith setting of their own
+    let mut arith = Arithmetic::Checked;
+    // The widths of isize and usize, if not that of the compilation target
+    let mut pointer_width = None;
     for arg in reg.args() {
-        if arg.clone().unwrap().node == MetaItemKind::Word(InternedString::new("debug")) {
-            debug = true;
-        }
-        else {
-            rp_error!("unrecognized plugin argument");
+        match arg.clone().unwrap().node {
+            MetaItemKind::Word(ref name) if name == "debug" => debug = true,
+            MetaItemKind::Word(ref name) if name == "check_all" => check_all = true,
+            MetaItemKind::Word(ref name) if name == "print_invariants" => print_invariants = true,
+            MetaItemKind::NameValue(ref name, ref literal) if name == "arith" => {
+                arith = parse_arithmetic(literal);
+            },
+            MetaItemKind::NameValue(ref name, ref literal) if name == "pointer_width" => {
+                pointer_width = Some(parse_pointer_width(literal));
+            },
+            _ => rp_error!("unrecognized plugin argument"),
         }
     }
 
-    let visitor = MirVisitor { debug: debug };
+    let visitor = MirVisitor {
+        debug: debug,
+        check_all: check_all,
+        print_invariants: print_invariants,
+        arith: arith,
+        pointer_width: pointer_width,
+        predicates: HashMap::new(),
+    };
 
     reg.register_attribute("condition".to_string(), AttributeType::Whitelisted);
+    reg.register_attribute("requires".to_string(), AttributeType::Whitelisted);
+    reg.register_attribute("ensures".to_string(), AttributeType::Whitelisted);
+    reg.register_attribute("panics_if".to_string(), AttributeType::Whitelisted);
+    reg.register_attribute("decreases".to_string(), AttributeType::Whitelisted);
+    reg.register_attribute("loop_condition".to_string(), AttributeType::Whitelisted);
+    reg.register_attribute("predicate".to_string(), AttributeType::Whitelisted);
+    reg.register_attribute("rustproof".to_string(), AttributeType::Whitelisted);
+    reg.register_attribute("equivalent_to".to_string(), AttributeType::Whitelisted);
     reg.register_mir_pass(Box::new(visitor));
 }
 
@@ -98,10 +134,51 @@ pub struct MirData<'tcx> {
     var_data: Vec<&'tcx VarDecl<'tcx>>,
     temp_data: Vec<&'tcx TempDecl<'tcx>>,
     func_return_type: Types,
+    // The lvalue referred to by each reference created in the function body
+    references: HashMap<String, VariableMappingData>,
+    // The weakest precondition of a path that panics: false, unless a panic condition is checked
+    panic_expr: Expression,
+    // Whether overflow panics or wraps around
+    arith: Arithmetic,
+    // The specification the function's calls to itself are verified against
+    contract: Option<Contract>,
+    // The loops of the function, by the index of their head
+    loops: HashMap<usize, Loop>,
+    // The heads of the loops whose iterations are being generated, innermost last
+    loop_stack: Vec<usize>,
+    // What the weakest precondition requires of the loops
+    goal: Goal,
+    // The number of iterations each loop is unrolled to, if it is not cut by its invariant
+    unroll: Option<usize>,
+    // The heads of the unrolled loops being iterated, with the iterations so far, innermost last
+    unwinding: Vec<(usize, usize)>,
+}
+
+/// The specification of a function, as seen by a call to it
+#[derive(Clone)]
+pub struct Contract {
+    // The path of the function, as it appears in a call
+    name: String,
+    // The arguments, which the conditions refer to
+    params: Vec<VariableMappingData>,
+    // What the caller must establish
+    pre: Expression,
+    // What the caller may assume afterwards, of the arguments and "return"
+    post: Expression,
+    // The termination measure, which each call must decrease
+    decreases: Option<Expression>,
 }
 
 // required struct for Pass impl
-struct MirVisitor { debug: bool }
+struct MirVisitor {
+    debug: bool,
+    check_all: bool,
+    print_invariants: bool,
+    arith: Arithmetic,
+    pointer_width: Option<Vec<u8>>,
+    // The functions marked #[predicate], by name
+    predicates: HashMap<String, Predicate>,
+}
 
 /// This must exist and must be blank
 impl <'tcx> Pass for MirVisitor {}
@@ -109,15 +186,40 @@ impl <'tcx> Pass for MirVisitor {}
 /// Sets up the compiler to go through MIR code.
 ///
 /// # Remarks:
-impl <'tcx> MirPass<'tcx> for MirVisitor {
-    // Visit the MIR of the entire program
-    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>) {
-        let debug = self.debug;
-        // Clear the stored attributes in the builder
-        let mut pre_string = "".to_string();
-        let mut post_string = "".to_string();
-        let pre_expr;
-        let post_expr;
+/// * The MIR of the whole crate is visited at once, so that every predicate is defined before the
+///   conditions that call it are parsed.
+/// * Functions are visited in the order they are defined.
+impl <'tcx> MirMapPass<'tcx> for MirVisitor {
+    fn run_pass<'a>(&mut self,
+                    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    map: &mut MirMap<'tcx>,
+                    _: &mut [Box<for<'s> MirPassHook<'s>>]) {
+        let mut ids: Vec<NodeId> = map.map.keys().cloned().collect();
+        ids.sort();
+
+        for id in ids.iter() {
+            if attr::contains_name(tcx.map.attrs(*id), "predicate") {
+                self.define_predicate(tcx, MirSource::from_node(tcx, *id), &map.map[id]);
+            }
+        }
+
+        for id in ids.iter() {
+            self.check_function(tcx, MirSource::from_node(tcx, *id), &map.map[id], map);
+        }
+    }
+}
+
+impl MirVisitor {
+    // Verifies a function against its specification attributes, if it has any, and checks that it
+    // is equivalent to the function named by its equivalent_to attribute. In check_all mode, a
+    // function without any is checked for the absence of panics and overflow.
+    fn check_function<'a, 'tcx>(&mut self,
+                                tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                src: MirSource,
+                                mir: &Mir<'tcx>,
+                                map: &MirMap<'tcx>) {
+        let mut clauses: Vec<Clause> = Vec::new();
+        let mut options: FunctionOptions = Default::default();
 
         // Store relevant data
         let item_id = src.item_id();
@@ -125,87 +227,928 @@ impl <'tcx> MirPass<'tcx> for MirVisitor {
         let name = tcx.item_path_str(def_id);
         let attrs = tcx.map.attrs(item_id);
 
-        // TODO: Find a better way to do this
         for attr in attrs {
-            parse_attribute(&mut pre_string, &mut post_string, attr);
+            parse_attribute(&mut clauses, attr);
+            parse_options(&mut options, attr);
         }
 
-        // TODO: Find a better condition check
-        if pre_string != "" {
-            // Parse the pre- and postcondition arguments
-            pre_expr = Some(parser::parse_condition(pre_string.as_str()));
-            post_expr = Some(parser::parse_condition(post_string.as_str()));
+        // The function is verified once for each width of isize and usize. The results are
+        // labelled with the width if there are several.
+        let widths = match options.pointer_width {
+            Some(ref widths) => widths.clone(),
+            None => self.pointer_widths(tcx),
+        };
+        let label = |width: u8| {
+            if widths.len() > 1 {
+                format!("{}[{}-bit]", name, width)
+            } else {
+                name.clone()
+            }
+        };
 
-            // Get the return type
-            let func_return_type: String = match mir.return_ty {
-                FnOutput::FnConverging(t) => {
-                    t.to_string()
-                },
-                _ => unimplemented!(),
-            };
+        if let Some(ref path) = options.equivalent_to {
+            let reference_id = find_function(tcx, map, path);
+            let mut reference_options: FunctionOptions = Default::default();
+            for attr in tcx.map.attrs(reference_id).iter() {
+                parse_options(&mut reference_options, attr);
+            }
+            for &width in widths.iter() {
+                set_pointer_width(width);
+                self.check_equivalence(label(width),
+                                       path,
+                                       &clauses,
+                                       &options,
+                                       &reference_options,
+                                       mir,
+                                       &map.map[&reference_id]);
+            }
+            if clauses.is_empty() {
+                return;
+            }
+        }
 
-            // Struct to carry MIR data to later stages
-            let mut data = MirData {
-                block_data: Vec::new(),
-                arg_data: Vec::new(),
-                var_data: Vec::new(),
-                temp_data: Vec::new(),
-                func_return_type: string_to_type(func_return_type),
-            };
+        if !clauses.is_empty() {
+            for &width in widths.iter() {
+                set_pointer_width(width);
+                self.verify_function(tcx, &name, label(width), &clauses, &options, mir, true);
+            }
+            return;
+        }
 
-            // Get the basic block data
-            for index in 0..mir.basic_blocks().len() {
-                let block = BasicBlock::new(index);
-                data.block_data.push(&mir[block]);
+        if !self.check_all {
+            return;
+        }
+        if !is_supported(mir) {
+            rp_warn!("Skipping {}: its arguments or return value have unsupported types", name);
+            return;
+        }
+        // Only problems are reported. Functions using unsupported constructs are skipped with a
+        // warning, and the panic hook is replaced so that they do not print a panic message. Any
+        // other panic is a bug, and is reported as usual.
+        let hook = Arc::new(panic::take_hook());
+        let default_hook = hook.clone();
+        panic::set_hook(Box::new(move |info| {
+            if unsupported_message(info.payload()).is_none() {
+                (**default_hook)(info);
+            }
+        }));
+        SKIP_UNSUPPORTED.with(|skip| skip.set(true));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            for &width in widths.iter() {
+                set_pointer_width(width);
+                self.verify_function(tcx, &name, label(width), &clauses, &options, mir, false);
             }
+        }));
+        SKIP_UNSUPPORTED.with(|skip| skip.set(false));
+        // Dropping the replacement hook drops its reference to the default one
+        drop(panic::take_hook());
+        match Arc::try_unwrap(hook) {
+            Ok(hook) => panic::set_hook(hook),
+            Err(_) => unreachable!(),
+        }
+        if let Err(payload) = result {
+            match unsupported_message(&*payload) {
+                Some(message) => rp_warn!("Skipping {}: {}", name, message),
+                None => panic::resume_unwind(payload),
+            }
+        }
+    }
+
+    // Verifies a function against its specification clauses. If there are none, the function is
+    // checked for the absence of panics and overflow.
+    fn verify_function<'a, 'tcx>(&mut self,
+                                 tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                 path: &str,
+                                 name: String,
+                                 clauses: &[Clause],
+                                 options: &FunctionOptions,
+                                 mir: &Mir<'tcx>,
+                                 report_valid: bool) {
+        let debug = self.debug;
+        let mut data = gen_mir_data(mir);
+        data.arith = options.arith.unwrap_or(self.arith);
+        data.unroll = options.unroll;
 
-            // Get the function argument declarations
-            for index in 0..mir.arg_decls.len() {
-                let arg = Arg::new(index);
-                data.arg_data.push(&mir.arg_decls[arg]);
+        // The arguments may appear in either condition; "return" only in the postcondition.
+        let mut env = gen_environment(&data);
+
+        // Parse the preconditions. Those of each case are conjoined, as are those shared by all
+        // cases.
+        let cases: usize = clauses.iter().filter_map(|c| c.case).map(|c| c + 1).max().unwrap_or(0);
+        let mut pre_exprs: Vec<Vec<Expression>> = vec![Vec::new(); cases + 1];
+        for clause in clauses.iter().filter(|c| c.kind == ClauseKind::Requires) {
+            let pre_expr = parser::parse_condition(clause.condition.as_str(),
+                                                   &env,
+                                                   &self.predicates);
+            if contains_old(&pre_expr) {
+                rp_error!("old() may only be used in a postcondition");
             }
+            // The shared preconditions are stored last
+            pre_exprs[clause.case.unwrap_or(cases)].push(pre_expr);
+        }
+        // A char argument holds a Unicode scalar value
+        for var in env.iter().filter(|v| v.var_type == Types::Char) {
+            pre_exprs[cases].push(valid_char(&Expression::VariableMapping(var.clone())));
+        }
+        let mut case_pres: Vec<Expression> = pre_exprs.into_iter().map(conjunction).collect();
+        let shared_pre = case_pres.pop().unwrap();
 
-            // Get the temp declarations
-            for index in 0..mir.temp_decls.len() {
-                let temp = Temp::new(index);
-                data.temp_data.push(&mir.temp_decls[temp]);
+        // Parse the panic conditions. The function must panic if any of those of a case, or any of
+        // those shared by all cases, holds.
+        let mut panic_clauses: Vec<Vec<(String, Expression)>> = vec![Vec::new(); cases + 1];
+        for clause in clauses.iter().filter(|c| c.kind == ClauseKind::PanicsIf) {
+            let label = format!("{} \"{}\" ({})",
+                                clause.kind,
+                                clause.condition,
+                                tcx.sess.codemap().span_to_string(clause.span));
+            let panic_expr = parser::parse_condition(clause.condition.as_str(),
+                                                     &env,
+                                                     &self.predicates);
+            if contains_old(&panic_expr) {
+                rp_error!("old() may only be used in a postcondition");
+            }
+            panic_clauses[clause.case.unwrap_or(cases)].push((label, panic_expr));
+        }
+        let panic_condition = |case: Option<usize>| -> Option<(String, Expression)> {
+            let mut scope: Vec<(String, Expression)> = panic_clauses[cases].clone();
+            if let Some(c) = case {
+                scope.extend(panic_clauses[c].iter().cloned());
             }
+            if scope.is_empty() {
+                return None;
+            }
+            let labels: Vec<String> = scope.iter().map(|&(ref l, _)| l.clone()).collect();
+            Some((labels.join(", "), disjunction(scope.into_iter().map(|(_, e)| e).collect())))
+        };
 
-            // Get the variable declarations
-            for index in 0..mir.var_decls.len() {
-                let var = Var::new(index);
-                data.var_data.push(&mir.var_decls[var]);
+        // Parse the termination measure, which refers to the arguments on entry
+        let mut measures = clauses.iter().filter(|c| c.kind == ClauseKind::Decreases);
+        let decreases = measures.next().map(|clause| {
+            let measure = parser::parse_condition(clause.condition.as_str(),
+                                                  &env,
+                                                  &self.predicates);
+            let measure_type = determine_evaluation_type(&measure);
+            if !is_signed_type(measure_type.clone()) && !is_unsigned_type(measure_type) {
+                rp_error!("The decreases measure \"{}\" must be an integer", clause.condition);
             }
+            measure
+        });
+        if measures.next().is_some() {
+            rp_error!("A function may have only one decreases measure");
+        }
 
-            if debug {
-                println!("Printing basic blocks...");
-                for index in 0..data.block_data.len() {
-                    println!("bb{:?}\n{:#?}\n", index, data.block_data[index]);
+        // Find the loops, and parse their invariants and variants, which may also refer to the
+        // local variables by name
+        let mut loops = find_loops(&data);
+        let locals = gen_local_environment(&data, &env);
+        let mut loop_env = env.clone();
+        loop_env.extend(locals.iter().map(|&(ref source, _)| source.clone()));
+        for clause in clauses.iter().filter(|c| c.loop_index.is_some()) {
+            let index = clause.loop_index.unwrap();
+            if index >= loops.len() {
+                rp_error!("{} ({}) describes loop {}, but the function has {} loop(s)",
+                          clause.kind,
+                          tcx.sess.codemap().span_to_string(clause.span),
+                          index + 1,
+                          loops.len());
+            }
+            let mut condition = parser::parse_condition(clause.condition.as_str(),
+                                                        &loop_env,
+                                                        &self.predicates);
+            for &(ref source, ref local) in locals.iter() {
+                substitute_variable_with_expression(&mut condition,
+                                                    source,
+                                                    &Expression::VariableMapping(local.clone()));
+            }
+            if clause.kind == ClauseKind::Invariant {
+                loops[index].invariant = Some(condition);
+            } else {
+                let variant_type = determine_evaluation_type(&condition);
+                if !is_signed_type(variant_type.clone()) && !is_unsigned_type(variant_type) {
+                    rp_error!("The variant \"{}\" must be an integer", clause.condition);
                 }
+                loops[index].variant = Some(condition);
             }
+        }
+        for l in loops.iter_mut() {
+            l.label = tcx.sess.codemap().span_to_string(
+                data.block_data[l.head].terminator().source_info.span
+            );
+        }
+        // Parse the postconditions, each of which is checked separately
+        let params = env.clone();
+        env.push(VariableMappingData {
+            name: "return".to_string(),
+            var_type: data.func_return_type.clone(),
+        });
+        let mut post_exprs: Vec<(Option<usize>, String, Expression)> = Vec::new();
+        // The postconditions a recursive call may assume, each under its case's preconditions
+        let mut contract_posts: Vec<Expression> = Vec::new();
+        for clause in clauses.iter().filter(|c| c.kind == ClauseKind::Ensures) {
+            let label = format!("{} \"{}\" ({})",
+                                clause.kind,
+                                clause.condition,
+                                tcx.sess.codemap().span_to_string(clause.span));
+            let post_expr = parser::parse_condition(clause.condition.as_str(),
+                                                    &env,
+                                                    &self.predicates);
+            contract_posts.push(match clause.case {
+                Some(case) => Expression::BinaryExpression( BinaryExpressionData{
+                    op: BinaryOperator::Implication,
+                    left: Box::new(case_pres[case].clone()),
+                    right: Box::new(post_expr.clone())
+                } ),
+                None => post_expr.clone(),
+            });
+            // A shared postcondition holds in every case
+            let post_cases: Vec<usize> = match clause.case {
+                Some(case) => vec![case],
+                None => (0..cases).collect(),
+            };
+            if post_cases.is_empty() {
+                post_exprs.push((None, label, post_expr));
+            }
+            for case in post_cases {
+                post_exprs.push((Some(case),
+                                 format!("case {}: {}", case + 1, label),
+                                 post_expr.clone()));
+            }
+        }
+        // Without a postcondition, a case must still not panic or overflow
+        for case in 0..cases {
+            if !post_exprs.iter().any(|&(c, _, _)| c == Some(case)) {
+                post_exprs.push((Some(case),
+                                 format!("case {}: the absence of panics and overflow", case + 1),
+                                 Expression::BooleanLiteral(true)));
+            }
+        }
+        if post_exprs.is_empty() {
+            post_exprs.push((None,
+                             "the absence of panics and overflow".to_string(),
+                             Expression::BooleanLiteral(true)));
+        }
+
+        if debug {
+            println!("Printing basic blocks...");
+            for index in 0..data.block_data.len() {
+                println!("bb{:?}\n{:#?}\n", index, data.block_data[index]);
+            }
+        }
+
+        // Calls to the function itself are verified against its specification. Some case must
+        // apply to the arguments of the call, if the function has cases.
+        let mut contract_pre = shared_pre.clone();
+        if cases > 0 {
+            contract_pre = conjunction(vec![contract_pre, disjunction(case_pres.clone())]);
+        }
+        data.contract = Some(Contract {
+            name: path.to_string(),
+            params: params.clone(),
+            pre: contract_pre.clone(),
+            post: conjunction(contract_posts),
+            decreases: decreases,
+        });
 
+        data.loops = loops.iter().map(|l| (l.head, l.clone())).collect();
+
+        // Resolve the references created in the function body
+        gen_references(&mut data);
+
+        // Loops without an invariant are given one inferred from candidates: the bounds found on
+        // their variables, and conditions built from templates. Only the candidates that can be
+        // proved are kept.
+        if data.unroll.is_none() && loops.iter().any(|l| l.invariant.is_none()) {
+            let mut names: Vec<(VariableMappingData, String)> =
+                params.iter().map(|p| (p.clone(), p.name.clone())).collect();
+            names.extend(locals.iter().map(|&(ref source, ref local)| {
+                (local.clone(), source.name.clone())
+            }));
+            let bounds = infer_invariants(&mut data, &names);
+            let templates = gen_templates(&mut data, &names, &params);
+            for l in loops.iter_mut().filter(|l| l.invariant.is_none()) {
+                let mut candidates: Vec<(Expression, String)> = bounds[&l.head].clone();
+                for template in templates.iter() {
+                    if !candidates.iter().any(|c| c.1 == template.1) {
+                        candidates.push(template.clone());
+                    }
+                }
+                l.candidates = candidates;
+                l.inferred = Some(String::new());
+            }
+            houdini(&mut data, &mut loops, &contract_pre, debug, &self.predicates);
+
+            if self.print_invariants {
+                println!("\nInferred loop invariants of fn {}(..):", name);
+                for l in loops.iter().filter(|l| l.inferred.is_some()) {
+                    println!("\t#[loop_condition(invariant=\"{}\")] for the loop at {}",
+                             l.inferred.as_ref().unwrap(),
+                             l.label);
+                }
+            }
+        }
+
+        let mut vcs: Vec<(String, Expression)> = Vec::new();
+        for (case, label, post_expr) in post_exprs {
             // Generate the weakest precondition
-            let weakest_precondition = gen(0, &mut data, &post_expr, debug);
+            let mut weakest_precondition = gen(0, &mut data, &Some(post_expr), debug);
+
+            // The weakest precondition describes the entry state, which old() refers to
+            remove_old(weakest_precondition.as_mut().unwrap());
+
+            // A case is checked under its own preconditions as well as the shared ones
+            let mut pre_expr = match case {
+                Some(c) => conjunction(vec![shared_pre.clone(), case_pres[c].clone()]),
+                None => shared_pre.clone(),
+            };
+            // The postcondition need only hold when the function does not panic
+            if let Some((_, panic_expr)) = panic_condition(case) {
+                pre_expr = conjunction(vec![pre_expr, Expression::UnaryExpression(
+                    UnaryExpressionData {
+                        op: UnaryOperator::Not,
+                        e: Box::new(panic_expr),
+                    }
+                )]);
+            }
 
             // Create the verification condition, P -> WP
             let verification_condition: Expression = Expression::BinaryExpression( BinaryExpressionData{
                 op: BinaryOperator::Implication,
-                left: Box::new(pre_expr.as_ref().unwrap().clone()),
-                right: Box::new(weakest_precondition.as_ref().unwrap().clone())
+                left: Box::new(pre_expr),
+                right: Box::new(weakest_precondition.unwrap())
             } );
+            vcs.push((label, verification_condition));
+        }
+
+        // Each loop invariant must hold on entry to its loop, and after each iteration, for any
+        // input allowed by the preconditions. Each variant must decrease on each iteration. The
+        // postconditions assume these, and are checked for panics and overflow on their own.
+        // An invariant may instead be checked by k-induction. Unrolled loops instead must each
+        // exit within the unrolled iterations.
+        data.panic_expr = Expression::BooleanLiteral(true);
+        for l in loops.iter() {
+            let mut goals: Vec<(Goal, String)> = Vec::new();
+            match data.unroll {
+                Some(bound) => {
+                    goals.push((Goal::Unwinding(l.head),
+                                format!("the unwinding of the loop at {} within {} iteration(s)",
+                                        l.label,
+                                        bound)));
+                },
+                None => {
+                    // Inferred invariants are inductive already
+                    match options.k_induction {
+                        Some(limit) if l.inferred.is_none() => {
+                            vcs.push(k_induction(&mut data,
+                                                 l,
+                                                 limit,
+                                                 &contract_pre,
+                                                 debug,
+                                                 &self.predicates));
+                        },
+                        _ => {
+                            let kind = if l.inferred.is_some() {
+                                "inferred invariant"
+                            } else {
+                                "invariant"
+                            };
+                            goals.push((Goal::Invariant(l.head),
+                                        format!("the {} of the loop at {}", kind, l.label)));
+                        },
+                    }
+                    if l.variant.is_some() {
+                        goals.push((Goal::Termination(l.head),
+                                    format!("the termination of the loop at {}", l.label)));
+                    }
+                },
+            }
+            for (goal, label) in goals {
+                data.goal = goal;
+                let mut loop_wp = gen(0, &mut data, &Some(Expression::BooleanLiteral(true)), debug)
+                                  .unwrap();
+                remove_old(&mut loop_wp);
+                vcs.push((label,
+                          Expression::BinaryExpression( BinaryExpressionData{
+                              op: BinaryOperator::Implication,
+                              left: Box::new(contract_pre.clone()),
+                              right: Box::new(loop_wp)
+                          } )));
+            }
+        }
+        data.goal = Goal::Postcondition;
+        data.panic_expr = Expression::BooleanLiteral(false);
+
+        // The function must panic exactly when its panic condition holds. The weakest precondition
+        // of the function panicking is generated with a postcondition of false, and a weakest
+        // precondition of true wherever it panics.
+        let scopes: Vec<Option<usize>> = if cases == 0 {
+            vec![None]
+        } else {
+            (0..cases).map(Some).collect()
+        };
+        let mut panic_wp: Option<Expression> = None;
+        for case in scopes {
+            if let Some((label, panic_expr)) = panic_condition(case) {
+                if panic_wp.is_none() {
+                    data.panic_expr = Expression::BooleanLiteral(true);
+                    panic_wp = gen(0, &mut data, &Some(Expression::BooleanLiteral(false)), debug);
+                    data.panic_expr = Expression::BooleanLiteral(false);
+                }
+                let (label, pre_expr) = match case {
+                    Some(c) => (format!("case {}: {}", c + 1, label),
+                                conjunction(vec![shared_pre.clone(), case_pres[c].clone()])),
+                    None => (label, shared_pre.clone()),
+                };
+                vcs.push((label,
+                          Expression::BinaryExpression( BinaryExpressionData{
+                              op: BinaryOperator::Implication,
+                              left: Box::new(pre_expr),
+                              right: Box::new(Expression::BinaryExpression( BinaryExpressionData{
+                                  op: BinaryOperator::BiImplication,
+                                  left: Box::new(panic_expr),
+                                  right: Box::new(panic_wp.clone().unwrap())
+                              } ))
+                          } )));
+            }
+        }
+
+        // The cases must cover every input allowed by the shared preconditions
+        if options.complete {
+            vcs.push(("the completeness of the cases".to_string(),
+                      Expression::BinaryExpression( BinaryExpressionData{
+                          op: BinaryOperator::Implication,
+                          left: Box::new(shared_pre.clone()),
+                          right: Box::new(disjunction(case_pres.clone()))
+                      } )));
+        }
 
+        // No input may satisfy the preconditions of two cases
+        if options.disjoint {
+            for i in 0..cases {
+                for j in (i + 1)..cases {
+                    let overlap = conjunction(vec![case_pres[i].clone(), case_pres[j].clone()]);
+                    vcs.push((format!("the disjointness of cases {} and {}", i + 1, j + 1),
+                              Expression::BinaryExpression( BinaryExpressionData{
+                                  op: BinaryOperator::Implication,
+                                  left: Box::new(shared_pre.clone()),
+                                  right: Box::new(Expression::UnaryExpression( UnaryExpressionData{
+                                      op: UnaryOperator::Not,
+                                      e: Box::new(overlap)
+                                  } ))
+                              } )));
+                }
+            }
+        }
+
+        for &(ref label, ref verification_condition) in vcs.iter() {
             // FIXME: Debug should not be a const; it must be user-facing
             if debug {
-                println!("vc: {}\n", verification_condition);
+                println!("vc for {}: {}\n", label, verification_condition);
             }
             // Check that the verification condition is correctly typed
-            match expression::ty_check(&verification_condition) {
+            match expression::ty_check(verification_condition) {
                 Ok(_) => {},
                 Err(e) => rp_error!("{}", e),
             }
+        }
+
+        // Output to SMT-LIB format
+        gen_smtlib(&vcs, name, debug, report_valid, &self.predicates);
+    }
+
+    // Checks that a function is equivalent to a reference function with the same signature: for
+    // any arguments allowed by its shared preconditions, either both panic, or neither does, and
+    // they return the same value and leave the same values behind their reference arguments. The
+    // weakest precondition of each function producing given outputs without panicking is
+    // generated, with the arguments of the reference function renamed to those of the function,
+    // and the two must be equivalent for any outputs.
+    fn check_equivalence<'tcx>(&self,
+                               name: String,
+                               path: &str,
+                               clauses: &[Clause],
+                               options: &FunctionOptions,
+                               reference_options: &FunctionOptions,
+                               mir: &Mir<'tcx>,
+                               reference_mir: &Mir<'tcx>) {
+        let debug = self.debug;
+        let mut data = gen_mir_data(mir);
+        let mut reference_data = gen_mir_data(reference_mir);
+        data.arith = options.arith.unwrap_or(self.arith);
+        reference_data.arith = reference_options.arith.unwrap_or(self.arith);
+
+        // The functions must take and return the same supported types
+        let params = gen_environment(&data);
+        let reference_params = gen_environment(&reference_data);
+        if params.len() != data.arg_data.len()
+           || reference_params.len() != reference_data.arg_data.len()
+           || params.len() != reference_params.len()
+           || params.iter().zip(reference_params.iter()).any(|(p, r)| {
+                  p.var_type != r.var_type || p.name.starts_with("*") != r.name.starts_with("*")
+              })
+           || data.func_return_type != reference_data.func_return_type {
+            rp_error!("fn {} and {} must take and return the same supported types to be checked \
+                      for equivalence", name, path);
+        }
+        // The weakest preconditions of loops cut by invariants only imply those of the functions
+        if !find_loops(&data).is_empty() || !find_loops(&reference_data).is_empty() {
+            rp_error!("fn {} and {} must not contain loops to be checked for equivalence",
+                      name,
+                      path);
+        }
+
+        // The outputs are the return value and the values behind the reference arguments, which
+        // are given the names of those of the function, with a suffix
+        let return_type = data.func_return_type.clone();
+        let mut outputs: Vec<usize> = (0..params.len()).filter(|&i| params[i].name.starts_with("*"))
+                                                       .collect();
+        if return_type != Types::Void {
+            outputs.push(params.len());
+        }
+        let output = |vars: &[VariableMappingData], i: usize| -> VariableMappingData {
+            if i < vars.len() {
+                vars[i].clone()
+            } else {
+                VariableMappingData {
+                    name: "return".to_string(),
+                    var_type: return_type.clone(),
+                }
+            }
+        };
+        let post = |vars: &[VariableMappingData]| -> Expression {
+            conjunction(outputs.iter().map(|&i| {
+                let var = output(vars, i);
+                let value = output(&params, i);
+                Expression::BinaryExpression( BinaryExpressionData{
+                    op: BinaryOperator::Equal,
+                    left: Box::new(Expression::VariableMapping(var)),
+                    right: Box::new(Expression::VariableMapping( VariableMappingData {
+                        name: value.name + "!out",
+                        var_type: value.var_type,
+                    } ))
+                } )
+            }).collect())
+        };
+        let post_expr = post(&params);
+        let reference_post = post(&reference_params);
+
+        gen_references(&mut data);
+        gen_references(&mut reference_data);
+        let wp = gen(0, &mut data, &Some(post_expr), debug).unwrap();
+        let mut reference_wp = gen(0, &mut reference_data, &Some(reference_post), debug).unwrap();
+        // The arguments are renamed in two steps, in case the functions share names in a different
+        // order
+        for (i, param) in reference_params.iter().enumerate() {
+            substitute_variable_with_expression(&mut reference_wp,
+                                                param,
+                                                &Expression::VariableMapping( VariableMappingData {
+                                                    name: format!("arg!{}", i),
+                                                    var_type: param.var_type.clone(),
+                                                } ));
+        }
+        for (i, param) in params.iter().enumerate() {
+            substitute_variable_with_expression(&mut reference_wp,
+                                                &VariableMappingData {
+                                                    name: format!("arg!{}", i),
+                                                    var_type: param.var_type.clone(),
+                                                },
+                                                &Expression::VariableMapping(param.clone()));
+        }
+
+        // The shared preconditions of the function restrict the arguments
+        let mut pre_exprs: Vec<Expression> = Vec::new();
+        let shared = clauses.iter().filter(|c| c.kind == ClauseKind::Requires && c.case.is_none());
+        for clause in shared {
+            let pre_expr = parser::parse_condition(clause.condition.as_str(),
+                                                   &params,
+                                                   &self.predicates);
+            if contains_old(&pre_expr) {
+                rp_error!("old() may only be used in a postcondition");
+            }
+            pre_exprs.push(pre_expr);
+        }
+        for var in params.iter().filter(|v| v.var_type == Types::Char) {
+            pre_exprs.push(valid_char(&Expression::VariableMapping(var.clone())));
+        }
+
+        let verification_condition = Expression::BinaryExpression( BinaryExpressionData{
+            op: BinaryOperator::Implication,
+            left: Box::new(conjunction(pre_exprs)),
+            right: Box::new(Expression::BinaryExpression( BinaryExpressionData{
+                op: BinaryOperator::BiImplication,
+                left: Box::new(wp),
+                right: Box::new(reference_wp)
+            } ))
+        } );
+        if debug {
+            println!("vc for the equivalence to {}: {}\n", path, verification_condition);
+        }
+        match expression::ty_check(&verification_condition) {
+            Ok(_) => {},
+            Err(e) => rp_error!("{}", e),
+        }
+
+        let vcs = vec![(format!("the equivalence to {}", path), verification_condition)];
+        gen_smtlib(&vcs, name, debug, true, &self.predicates);
+    }
+
+    // Records a function marked #[predicate], defined by the weakest precondition of its body
+    // returning true
+    fn define_predicate<'a, 'tcx>(&mut self,
+                                  tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                  src: MirSource,
+                                  mir: &Mir<'tcx>) {
+        let item_id = src.item_id();
+        let def_id = tcx.map.local_def_id(item_id);
+        let name = tcx.item_name(def_id).as_str().to_string();
+
+        if is_builtin_function(name.as_str()) {
+            rp_error!("Predicate {} has the name of a built-in function", name);
+        }
+        if self.predicates.contains_key(&name) {
+            rp_error!("Predicate {} is defined more than once", name);
+        }
+
+        // Predicates are defined for the first width the crate is verified for
+        set_pointer_width(self.pointer_widths(tcx)[0]);
+        let mut data = gen_mir_data(mir);
+        data.arith = self.arith;
+        if data.func_return_type != Types::Bool {
+            rp_error!("Predicate {} must return bool", name);
+        }
+        let params = gen_environment(&data);
+        if params.len() != data.arg_data.len()
+           || params.iter().any(|p| p.name.starts_with("*")) {
+            rp_error!("Predicate {} must take only integer and bool arguments", name);
+        }
+
+        gen_references(&mut data);
+        let result = Some(Expression::VariableMapping( VariableMappingData {
+            name: "return".to_string(),
+            var_type: Types::Bool,
+        }));
+        let body = gen(0, &mut data, &result, self.debug).unwrap();
+
+        if self.debug {
+            println!("predicate {}: {}\n", name, body);
+        }
+        self.predicates.insert(name, Predicate { params: params, body: body });
+    }
+
+    // Returns the widths of isize and usize that functions are verified for, unless they have a
+    // pointer_width setting of their own: those of the plugin argument, or that of the target.
+    fn pointer_widths<'a, 'tcx>(&self, tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Vec<u8> {
+        match self.pointer_width {
+            Some(ref widths) => widths.clone(),
+            None => {
+                match tcx.sess.target.target.target_pointer_width.parse::<u8>() {
+                    Ok(width) => vec![width],
+                    Err(_) => rp_error!("Unsupported target pointer width"),
+                }
+            },
+        }
+    }
+}
+
+// Returns the function with the given path, or with a path ending in it if there is only one
+fn find_function<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, map: &MirMap<'tcx>, path: &str) -> NodeId {
+    let mut ids: Vec<NodeId> = map.map.keys().cloned().collect();
+    ids.sort();
+    let paths: Vec<(NodeId, String)> = ids.into_iter().map(|id| {
+        let item_id = MirSource::from_node(tcx, id).item_id();
+        (id, tcx.item_path_str(tcx.map.local_def_id(item_id)))
+    }).collect();
+    if let Some(&(id, _)) = paths.iter().find(|&&(_, ref p)| p == path) {
+        return id;
+    }
+    let suffix = format!("::{}", path);
+    let matches: Vec<NodeId> = paths.iter()
+                                    .filter(|&&(_, ref p)| p.ends_with(suffix.as_str()))
+                                    .map(|&(id, _)| id)
+                                    .collect();
+    match matches.len() {
+        1 => matches[0],
+        0 => rp_error!("equivalent_to names {}, which is not a function of this crate", path),
+        _ => rp_error!("equivalent_to names {}, which is ambiguous; give its full path", path),
+    }
+}
+
+// Collects the MIR data of a function
+fn gen_mir_data<'a, 'tcx>(mir: &'a Mir<'tcx>) -> MirData<'a> {
+    // Get the return type
+    let func_return_type: String = match mir.return_ty {
+        FnOutput::FnConverging(t) => {
+            t.to_string()
+        },
+        _ => rp_unsupported!("Unsupported diverging function"),
+    };
+
+    // Struct to carry MIR data to later stages
+    let mut data = MirData {
+        block_data: Vec::new(),
+        arg_data: Vec::new(),
+        var_data: Vec::new(),
+        temp_data: Vec::new(),
+        func_return_type: string_to_type(func_return_type),
+        references: HashMap::new(),
+        panic_expr: Expression::BooleanLiteral(false),
+        arith: Arithmetic::Checked,
+        contract: None,
+        loops: HashMap::new(),
+        loop_stack: Vec::new(),
+        goal: Goal::Postcondition,
+        unroll: None,
+        unwinding: Vec::new(),
+    };
 
-            // Output to SMT-LIB format
-            gen_smtlib(&verification_condition, name, debug);
+    // Get the basic block data
+    for index in 0..mir.basic_blocks().len() {
+        let block = BasicBlock::new(index);
+        data.block_data.push(&mir[block]);
+    }
+
+    // Get the function argument declarations
+    for index in 0..mir.arg_decls.len() {
+        let arg = Arg::new(index);
+        data.arg_data.push(&mir.arg_decls[arg]);
+    }
+
+    // Get the temp declarations
+    for index in 0..mir.temp_decls.len() {
+        let temp = Temp::new(index);
+        data.temp_data.push(&mir.temp_decls[temp]);
+    }
+
+    // Get the variable declarations
+    for index in 0..mir.var_decls.len() {
+        let var = Var::new(index);
+        data.var_data.push(&mir.var_decls[var]);
+    }
+
+    data
+}
+
+// Checks whether the argument and return types of a function are supported
+fn is_supported(mir: &Mir) -> bool {
+    match mir.return_ty {
+        FnOutput::FnConverging(ty) => {
+            is_supported_type(ty) && mir.arg_decls.iter().all(|arg| is_supported_type(arg.ty))
+        },
+        FnOutput::FnDiverging => false,
+    }
+}
+
+// Returns the reason a function was skipped, if a panic comes from an unsupported construct
+// reported by rp_unsupported!
+fn unsupported_message(payload: &(Any + Send)) -> Option<String> {
+    match payload.downcast_ref::<Unsupported>() {
+        Some(&Unsupported(ref message)) => Some(message.clone()),
+        None => None,
+    }
+}
+
+// Checks whether a type, or the type a reference points to, is supported
+fn is_supported_type(ty: Ty) -> bool {
+    let ty = match ty.sty {
+        TypeVariants::TyRef(_, ref tm) => tm.ty,
+        _ => ty,
+    };
+    try_string_to_type(ty.to_string().as_str()).is_some()
+}
+
+// Returns the arguments a condition may refer to. The value behind a reference argument x is
+// referred to as *x.
+fn gen_environment(data: &MirData) -> Vec<VariableMappingData> {
+    let mut env: Vec<VariableMappingData> = Vec::new();
+    for arg in data.arg_data.iter() {
+        let (name, type_string) = match arg.ty.sty {
+            TypeVariants::TyRef(_, ref tm) => {
+                ("*".to_string() + arg.debug_name.as_str(), tm.ty.to_string())
+            },
+            _ => (arg.debug_name.as_str().to_string(), arg.ty.to_string()),
+        };
+        // Arguments of unsupported types cannot be referred to
+        if let Some(t) = try_string_to_type(type_string.as_str()) {
+            env.push(VariableMappingData { name: name, var_type: t });
+        }
+    }
+    env
+}
+
+// Gives each loop whose invariant is inferred the conjunction of the candidates for it that can be
+// proved. The candidates that cannot be proved, each assuming all the others, are dropped, until
+// those left can all be proved (the Houdini algorithm). Each must hold on entry to its loop, for
+// any input allowed by the preconditions, and after each iteration.
+fn houdini(data: &mut MirData,
+           loops: &mut Vec<Loop>,
+           pre: &Expression,
+           debug: bool,
+           predicates: &HashMap<String, Predicate>) {
+    data.panic_expr = Expression::BooleanLiteral(true);
+    loop {
+        for l in loops.iter_mut().filter(|l| l.inferred.is_some()) {
+            l.invariant = Some(conjunction(l.candidates.iter().map(|c| c.0.clone()).collect()));
+        }
+        data.loops = loops.iter().map(|l| (l.head, l.clone())).collect();
+
+        let mut failed: Vec<(usize, usize)> = Vec::new();
+        for l in loops.iter() {
+            for k in 0..l.candidates.len() {
+                data.goal = Goal::Candidate(l.head, k);
+                let mut loop_wp = gen(0, data, &Some(Expression::BooleanLiteral(true)), debug)
+                                  .unwrap();
+                remove_old(&mut loop_wp);
+                let verification_condition = Expression::BinaryExpression( BinaryExpressionData{
+                    op: BinaryOperator::Implication,
+                    left: Box::new(pre.clone()),
+                    right: Box::new(loop_wp)
+                } );
+                if !is_valid(&verification_condition, debug, predicates) {
+                    failed.push((l.head, k));
+                }
+            }
+        }
+        if failed.is_empty() {
+            break;
+        }
+        for l in loops.iter_mut() {
+            let head = l.head;
+            let kept: Vec<(Expression, String)> = l.candidates.iter()
+                                                   .enumerate()
+                                                   .filter(|&(k, _)| !failed.contains(&(head, k)))
+                                                   .map(|(_, c)| c.clone())
+                                                   .collect();
+            l.candidates = kept;
+        }
+    }
+    data.goal = Goal::Postcondition;
+    data.panic_expr = Expression::BooleanLiteral(false);
+
+    for l in loops.iter_mut().filter(|l| l.inferred.is_some()) {
+        let texts: Vec<String> = l.candidates.iter().map(|c| c.1.clone()).collect();
+        l.inferred = Some(if texts.is_empty() { "true".to_string() } else { texts.join(" && ") });
+    }
+    data.loops = loops.iter().map(|l| (l.head, l.clone())).collect();
+}
+
+// Checks the invariant of a loop by k-induction, for increasing k up to a limit: the invariant must
+// hold the first k times the loop's head is reached (the base case), and after any k consecutive
+// iterations that each start in a state satisfying it (the inductive step). Returns the
+// verification condition to report, with its label: the inductive step at the k the invariant is
+// proved for, or else the base case that fails, or the inductive step at the limit.
+fn k_induction(data: &mut MirData,
+               l: &Loop,
+               limit: usize,
+               pre: &Expression,
+               debug: bool,
+               predicates: &HashMap<String, Predicate>)
+               -> (String, Expression) {
+    let mut step: Option<Expression> = None;
+    for k in 1..(limit + 1) {
+        for &goal in [Goal::BaseCase(l.head, k), Goal::InductiveStep(l.head, k)].iter() {
+            data.goal = goal;
+            let mut loop_wp = gen(0, data, &Some(Expression::BooleanLiteral(true)), debug).unwrap();
+            remove_old(&mut loop_wp);
+            let verification_condition = Expression::BinaryExpression( BinaryExpressionData{
+                op: BinaryOperator::Implication,
+                left: Box::new(pre.clone()),
+                right: Box::new(loop_wp)
+            } );
+            let valid = is_valid(&verification_condition, debug, predicates);
+            match goal {
+                Goal::BaseCase(..) if !valid => {
+                    return (format!("the invariant of the loop at {}, the first {} time(s) it is \
+                                     reached", l.label, k),
+                            verification_condition);
+                },
+                Goal::InductiveStep(..) if valid => {
+                    return (format!("the invariant of the loop at {}, by {}-induction", l.label, k),
+                            verification_condition);
+                },
+                Goal::InductiveStep(..) => step = Some(verification_condition),
+                _ => {},
+            }
+        }
+    }
+    (format!("the invariant of the loop at {}, by k-induction for k up to {}", l.label, limit),
+     step.unwrap())
+}
+
+// Returns the local variables that loop conditions may refer to, each with the name it is written
+// with and the name it has in the weakest precondition. A variable that shares its name with an
+// argument or another variable cannot be referred to.
+fn gen_local_environment(data: &MirData, env: &[VariableMappingData])
+                         -> Vec<(VariableMappingData, VariableMappingData)> {
+    let mut locals: Vec<(VariableMappingData, VariableMappingData)> = Vec::new();
+    for (index, var) in data.var_data.iter().enumerate() {
+        let name = var.name.as_str().to_string();
+        let declared = data.var_data.iter().filter(|v| v.name.as_str() == name.as_str()).count();
+        let shared = declared > 1 || env.iter().any(|v| v.name == name);
+        if shared {
+            continue;
+        }
+        if let Some(t) = try_string_to_type(var.ty.to_string().as_str()) {
+            locals.push((VariableMappingData { name: name, var_type: t.clone() },
+                         VariableMappingData { name: format!("var{}", index), var_type: t }));
         }
     }
+    locals
 }
@@ -14,64 +14,355 @@ extern crate syntax;
 
 mod expression_parser;
 
-use syntax::ast::{MetaItemKind, Attribute_};
-use syntax::codemap::Spanned;
-use expression::{Expression, ty_check};
+use syntax::ast::{MetaItemKind, Attribute_, Lit, LitKind};
+use syntax::codemap::{Span, Spanned};
+use std::collections::HashMap;
+use lalrpop_util::ParseError;
+use std::fmt;
+use expression::{Expression, Predicate, VariableMappingData, infer_types, resolve_predicates,
+                 ty_check};
 
-/// Analyzes an attribute on a function in the compiled code, and if the attribute is "condition",
-/// ensures correct usage. If usage is correct, it stores the argument strings.
+/// Whether a clause of a function's specification is a precondition, a postcondition, the
+/// condition under which the function panics, the measure that its recursive calls decrease, or
+/// the invariant or variant of one of its loops
+#[derive(Clone, PartialEq)]
+pub enum ClauseKind {
+    Requires,
+    Ensures,
+    PanicsIf,
+    Decreases,
+    Invariant,
+    Variant,
+}
+
+impl fmt::Display for ClauseKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ClauseKind::Requires => write!(f, "requires"),
+            ClauseKind::Ensures => write!(f, "ensures"),
+            ClauseKind::PanicsIf => write!(f, "panics_if"),
+            ClauseKind::Decreases => write!(f, "decreases"),
+            ClauseKind::Invariant => write!(f, "invariant"),
+            ClauseKind::Variant => write!(f, "variant"),
+        }
+    }
+}
+
+/// One condition of a function's specification, as written by the user
+#[derive(Clone)]
+pub struct Clause {
+    pub kind: ClauseKind,
+    pub condition: String,
+    // The attribute the clause was written in
+    pub span: Span,
+    // The index of the specification case the clause belongs to, or None if it belongs to all
+    pub case: Option<usize>,
+    // The index of the loop an invariant or variant belongs to, in the order the loops are written
+    pub loop_index: Option<usize>,
+}
+
+/// How integer arithmetic that overflows behaves
+#[derive(Clone, Copy, PartialEq)]
+pub enum Arithmetic {
+    // Overflow panics, as in debug builds
+    Checked,
+    // Overflow wraps around, as in builds with overflow checks turned off
+    Wrapping,
+}
+
+/// Per-function settings given in a `#[rustproof(..)]` attribute
+#[derive(Clone, Default)]
+pub struct FunctionOptions {
+    // Whether the preconditions of the cases must cover every input allowed by the shared
+    // preconditions
+    pub complete: bool,
+    // Whether the preconditions of the cases must not overlap
+    pub disjoint: bool,
+    // The arithmetic semantics of the function, if not those of the crate
+    pub arith: Option<Arithmetic>,
+    // The widths of isize and usize to verify the function for, if not those of the crate
+    pub pointer_width: Option<Vec<u8>>,
+    // The number of iterations each loop is unrolled to, instead of being verified with its
+    // invariant
+    pub unroll: Option<usize>,
+    // The largest k for which the loop invariants are checked by k-induction, instead of only
+    // being checked to be inductive
+    pub k_induction: Option<usize>,
+    // The path of the function this one must be equivalent to, given by an equivalent_to attribute
+    pub equivalent_to: Option<String>,
+}
+
+/// Analyzes an attribute on a function in the compiled code, and if it is a specification
+/// attribute, ensures correct usage. If usage is correct, it stores the clauses it contains.
 ///
 /// # Arguments:
-/// * `pre_string` - Empty string. Will contain a user-submitted precondition if found.
-/// * `post_string` - Empty string. Will contain a user-submitted postcondition if found.
+/// * `clauses` - The clauses found so far. Will have any clauses in `attr` appended.
 /// * `attr` - The attribute being analyzed.
 ///
 /// # Remarks:
-/// * Currently supported `ConstInt`: `i8`, `i16`, `i32`, `i64`, `u8`, `u16`, `u32`, `u64`, `bool`
+/// * `#[condition(pre="P", post="Q", panics_if="R")]` is a specification case, containing a
+///   `requires`, an `ensures`, and a `panics_if` clause. Any of the arguments may be left out, and
+///   they may be given in any order.
+/// * `#[requires="P"]`, `#[ensures="Q"]` and `#[panics_if="R"]` contain a single clause, shared by
+///   all cases.
+/// * `#[decreases="M"]` gives the integer measure that each recursive call must decrease.
+/// * `#[loop_condition(invariant="I", variant="V")]` gives the invariant of a loop, and the integer
+///   measure that each iteration must decrease. Each attribute describes the next loop, in the
+///   order the loops are written.
+/// * A function may have any number of specification attributes.
 ///
-pub fn parse_attribute(pre_string: &mut String,
-                       post_string: &mut String,
-                       attr: &Spanned<Attribute_>) {
-    if let MetaItemKind::List(ref attribute_name, ref args) = attr.node.value.node {
-        // Ignore if not a condition attribute
-        if attribute_name == "condition" {
-            // Only accept if exactly 2 arguments
-            if args.len() != 2 {
-                rp_error!("Condition attribute must have exactly 2 arguments.");
+pub fn parse_attribute(clauses: &mut Vec<Clause>, attr: &Spanned<Attribute_>) {
+    match attr.node.value.node {
+        MetaItemKind::List(ref attribute_name, ref args) if attribute_name == "condition" => {
+            if args.len() == 0 {
+                rp_error!("Condition attribute must have a \"pre\", \"post\" or \"panics_if\" \
+                          argument.");
             }
-            // Parse the first argument
-            if let MetaItemKind::NameValue(ref i_string, ref literal) = args[0].node {
-                if i_string != "pre" {
-                    rp_error!( "The first argument must be named \"pre\". {} was provided.",
-                               i_string);
-                }
-                // Get the argument
-                if let syntax::ast::LitKind::Str(ref i_string, _) = literal.node {
-                    *pre_string = i_string.to_string();
+            // Cases are numbered in the order they are written
+            let case = clauses.iter().filter_map(|c| c.case).map(|c| c + 1).max().unwrap_or(0);
+            let mut found: Vec<ClauseKind> = Vec::new();
+            for arg in args.iter() {
+                if let MetaItemKind::NameValue(ref i_string, ref literal) = arg.node {
+                    let kind = match &**i_string {
+                        "pre" => ClauseKind::Requires,
+                        "post" => ClauseKind::Ensures,
+                        "panics_if" => ClauseKind::PanicsIf,
+                        _ => {
+                            rp_error!("The arguments must be named \"pre\", \"post\" and \
+                                      \"panics_if\". {} was provided.",
+                                      i_string);
+                        },
+                    };
+                    if found.contains(&kind) {
+                        rp_error!("The argument {} may only be given once.", i_string);
+                    }
+                    found.push(kind.clone());
+                    clauses.push(Clause {
+                        kind: kind,
+                        condition: condition_string(literal),
+                        span: attr.span,
+                        case: Some(case),
+                        loop_index: None,
+                    });
                 } else {
-                    rp_error!("Conditions must be strings. \
-                              Try wrapping conditions in quotation marks.");
+                    rp_error!("The arguments must be named \"pre\", \"post\" and \
+                              \"panics_if\".");
                 }
-            } else {
-                        rp_error!("The first argument must be named \"pre\".");
             }
-            // Parse the second argument
-            if let MetaItemKind::NameValue(ref i_string, ref literal) = args[1].node {
-                if i_string != "post" {
-                    rp_error!( "The second argument must be named \"post\". {} was provided.",
-                               i_string);
-                }
-                // Get the argument
-                if let syntax::ast::LitKind::Str(ref i_string, _) = literal.node {
-                    *post_string = i_string.to_string();
+        },
+        MetaItemKind::List(ref attribute_name, ref args) if attribute_name == "loop_condition" => {
+            if args.len() == 0 {
+                rp_error!("Loop condition attribute must have an \"invariant\" or \"variant\" \
+                          argument.");
+            }
+            // Loops are numbered in the order they are written
+            let loop_index = clauses.iter()
+                                    .filter_map(|c| c.loop_index)
+                                    .map(|l| l + 1)
+                                    .max()
+                                    .unwrap_or(0);
+            let mut found: Vec<ClauseKind> = Vec::new();
+            for arg in args.iter() {
+                if let MetaItemKind::NameValue(ref i_string, ref literal) = arg.node {
+                    let kind = match &**i_string {
+                        "invariant" => ClauseKind::Invariant,
+                        "variant" => ClauseKind::Variant,
+                        _ => {
+                            rp_error!("The arguments must be named \"invariant\" and \
+                                      \"variant\". {} was provided.",
+                                      i_string);
+                        },
+                    };
+                    if found.contains(&kind) {
+                        rp_error!("The argument {} may only be given once.", i_string);
+                    }
+                    found.push(kind.clone());
+                    clauses.push(Clause {
+                        kind: kind,
+                        condition: condition_string(literal),
+                        span: attr.span,
+                        case: None,
+                        loop_index: Some(loop_index),
+                    });
                 } else {
-                    rp_error!("Conditions must be strings. \
-                              Try wrapping conditions in quotation marks.");
+                    rp_error!("The arguments must be named \"invariant\" and \"variant\".");
+                }
+            }
+        },
+        MetaItemKind::NameValue(ref attribute_name, ref literal)
+            if attribute_name == "requires" || attribute_name == "ensures"
+               || attribute_name == "panics_if" || attribute_name == "decreases" => {
+            clauses.push(Clause {
+                kind: match &**attribute_name {
+                    "requires" => ClauseKind::Requires,
+                    "ensures" => ClauseKind::Ensures,
+                    "panics_if" => ClauseKind::PanicsIf,
+                    _ => ClauseKind::Decreases,
+                },
+                condition: condition_string(literal),
+                span: attr.span,
+                case: None,
+                loop_index: None,
+            });
+        },
+        MetaItemKind::Word(ref attribute_name) | MetaItemKind::List(ref attribute_name, _)
+            if attribute_name == "requires" || attribute_name == "ensures"
+               || attribute_name == "panics_if" || attribute_name == "decreases" => {
+            rp_error!("Specification clauses are written as #[{}=\"...\"].", attribute_name);
+        },
+        // Ignore if not a specification attribute
+        _ => {},
+    }
+}
+
+/// Analyzes an attribute on a function in the compiled code, and if it is a `rustproof` or
+/// `equivalent_to` attribute, stores the settings it contains.
+///
+/// # Arguments:
+/// * `options` - The settings found so far. Will be updated with any settings in `attr`.
+/// * `attr` - The attribute being analyzed.
+///
+/// # Remarks:
+/// * Currently supported settings: `complete`, `disjoint`, `arith="checked"`, `arith="wrapping"`,
+///   `pointer_width="..."`, `unroll="k"`, `k_induction="k"`
+/// * The function to check equivalence with is given by `#[equivalent_to(name)]`, or by
+///   `#[equivalent_to="path::to::name"]` for a path, which the compiler does not accept as a word.
+///
+pub fn parse_options(options: &mut FunctionOptions, attr: &Spanned<Attribute_>) {
+    match attr.node.value.node {
+        MetaItemKind::List(ref attribute_name, ref args) if attribute_name == "equivalent_to" => {
+            match args.first().map(|arg| &arg.node) {
+                Some(&MetaItemKind::Word(ref path)) if args.len() == 1 => {
+                    options.equivalent_to = Some(path.to_string());
+                },
+                _ => rp_error!("equivalent_to must name a single function."),
+            }
+        },
+        MetaItemKind::NameValue(ref attribute_name, ref literal)
+            if attribute_name == "equivalent_to" => {
+            match literal.node {
+                LitKind::Str(ref path, _) if !path.trim().is_empty() => {
+                    options.equivalent_to = Some(path.trim().to_string());
+                },
+                _ => rp_error!("equivalent_to must name a single function."),
+            }
+        },
+        _ => {},
+    }
+    if let MetaItemKind::List(ref attribute_name, ref args) = attr.node.value.node {
+        // Ignore if not a rustproof attribute
+        if attribute_name == "rustproof" {
+            for arg in args.iter() {
+                match arg.node {
+                    MetaItemKind::Word(ref setting) if setting == "complete" => {
+                        options.complete = true;
+                    },
+                    MetaItemKind::Word(ref setting) if setting == "disjoint" => {
+                        options.disjoint = true;
+                    },
+                    MetaItemKind::NameValue(ref setting, ref literal) if setting == "arith" => {
+                        options.arith = Some(parse_arithmetic(literal));
+                    },
+                    MetaItemKind::NameValue(ref setting, ref literal)
+                        if setting == "pointer_width" => {
+                        options.pointer_width = Some(parse_pointer_width(literal));
+                    },
+                    MetaItemKind::NameValue(ref setting, ref literal) if setting == "unroll" => {
+                        options.unroll = Some(parse_bound(literal, setting));
+                    },
+                    MetaItemKind::NameValue(ref setting, ref literal)
+                        if setting == "k_induction" => {
+                        options.k_induction = Some(parse_bound(literal, setting));
+                    },
+                    _ => rp_error!("Unrecognized rustproof setting."),
                 }
-            } else {
-                rp_error!("The second argument must be named \"post\".");
             }
-        } // Ignore if not a condition attribute
+        }
+    }
+}
+
+/// Reads the arithmetic semantics named by the value of an `arith` setting.
+///
+/// # Arguments:
+/// * `literal` - The value of the setting.
+///
+/// # Return:
+/// * The arithmetic semantics, if `literal` is `"checked"` or `"wrapping"`.
+///
+pub fn parse_arithmetic(literal: &Lit) -> Arithmetic {
+    if let LitKind::Str(ref i_string, _) = literal.node {
+        match &**i_string {
+            "checked" => return Arithmetic::Checked,
+            "wrapping" => return Arithmetic::Wrapping,
+            _ => {},
+        }
+    }
+    rp_error!("The arith setting must be \"checked\" or \"wrapping\".");
+}
+
+/// Reads the widths of `isize` and `usize` listed by the value of a `pointer_width` setting.
+///
+/// # Arguments:
+/// * `literal` - The value of the setting.
+///
+/// # Return:
+/// * The widths, if `literal` is a comma-separated list of `"16"`, `"32"` and `"64"`.
+///
+/// # Remarks:
+/// * A function is verified once for each width, e.g. `pointer_width="32, 64"` proves it for both
+///   32- and 64-bit targets.
+///
+pub fn parse_pointer_width(literal: &Lit) -> Vec<u8> {
+    if let LitKind::Str(ref i_string, _) = literal.node {
+        let mut widths: Vec<u8> = Vec::new();
+        for width in i_string.split(',') {
+            match width.trim() {
+                "16" => widths.push(16),
+                "32" => widths.push(32),
+                "64" => widths.push(64),
+                _ => {
+                    widths.clear();
+                    break;
+                },
+            }
+        }
+        if !widths.is_empty() {
+            return widths;
+        }
+    }
+    rp_error!("The pointer_width setting must list widths of \"16\", \"32\" or \"64\".");
+}
+
+/// Reads the number of iterations given by the value of an `unroll` or `k_induction` setting.
+///
+/// # Arguments:
+/// * `literal` - The value of the setting.
+/// * `setting` - The name of the setting, for reporting.
+///
+/// # Return:
+/// * The number of iterations, if `literal` is a positive integer, or a string holding one.
+///
+/// # Remarks:
+/// * Compilers without literals in attributes only accept the string form, e.g. `unroll="8"`.
+///
+pub fn parse_bound(literal: &Lit, setting: &str) -> usize {
+    let bound = match literal.node {
+        LitKind::Str(ref i_string, _) => i_string.trim().parse::<usize>().ok(),
+        LitKind::Int(value, _) => Some(value as usize),
+        _ => None,
+    };
+    match bound {
+        Some(k) if k > 0 => k,
+        _ => rp_error!("The {} setting must be a positive number of iterations.", setting),
+    }
+}
+
+// Returns the condition in a string literal
+fn condition_string(literal: &Lit) -> String {
+    if let LitKind::Str(ref i_string, _) = literal.node {
+        i_string.to_string()
+    } else {
+        rp_error!("Conditions must be strings. Try wrapping conditions in quotation marks.");
     }
 }
 
@@ -79,20 +370,35 @@ pub fn parse_attribute(pre_string: &mut String,
 ///
 /// # Arguments:
 /// * `condition` - A user-submitted string
+/// * `env` - The variables the condition may refer to, with their declared types
+/// * `predicates` - The predicates the condition may call, by name
 ///
 /// # Return:
 /// * If `condition` is valid, an Expression representing it.
 ///
 /// # Remarks:
 /// * Current supported types: `i8`, `i16`, `i32`, `i64`, `u8`, `u16`, `u32`, `u64`, `bool`
-pub fn parse_condition(condition: &str) -> Expression {
-    match expression_parser::parse_E1(condition) {
-        Ok(e) => {
+/// * Untyped variables and literals are given their types by `infer_types()`
+/// * Calls to predicates are checked against the predicates' parameter types
+pub fn parse_condition(condition: &str,
+                       env: &[VariableMappingData],
+                       predicates: &HashMap<String, Predicate>)
+                       -> Expression {
+    match expression_parser::parse_E0(condition) {
+        Ok(mut e) => {
+            resolve_predicates(&mut e, predicates);
+            if let Err(s) = infer_types(&mut e, env) {
+                rp_error!("Error in condition \"{}\": {}", condition, s);
+            }
             match ty_check(&e) {
                 Ok(_) => return e,
                 Err(s) => rp_error!("{}", s),
             }
         },
+        // An invalid literal
+        Err(ParseError::User { error }) => {
+            rp_error!("Error in condition \"{}\": {}", condition, error)
+        },
         Err(e) => rp_error!("Error parsing condition \"{}\": {:?}", condition, e)
     }
 }
@@ -10,6 +10,8 @@
 
 //! Prints user-facing errors and warnings.
 
+use std::cell::Cell;
+
 // Warning macro
 macro_rules! rp_warn {
     ($fmt:expr) => ({
@@ -57,3 +59,29 @@ macro_rules! rp_error {
         process::exit(1);
     });
 }
+
+// Whether an unsupported construct skips the function being verified, instead of being an error.
+// Set while unannotated functions are checked in check_all mode.
+thread_local!(pub static SKIP_UNSUPPORTED: Cell<bool> = Cell::new(false));
+
+/// The panic payload that skips a function using an unsupported construct
+pub struct Unsupported(pub String);
+
+// Unsupported construct macro. Skips the function being verified in check_all mode, and is an error
+// otherwise.
+macro_rules! rp_unsupported {
+    ($fmt:expr) => ({
+        use reporting::{SKIP_UNSUPPORTED, Unsupported};
+        if SKIP_UNSUPPORTED.with(|skip| skip.get()) {
+            panic!(Unsupported($fmt.to_string()));
+        }
+        rp_error!($fmt);
+    });
+    ($fmt:expr, $($arg:tt)*) => ({
+        use reporting::{SKIP_UNSUPPORTED, Unsupported};
+        if SKIP_UNSUPPORTED.with(|skip| skip.get()) {
+            panic!(Unsupported(format!($fmt, $($arg)*)));
+        }
+        rp_error!($fmt, $($arg)*);
+    });
+}
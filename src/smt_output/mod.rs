@@ -10,56 +10,620 @@
 
 //! Interface between rustproof and libsmt(z3).
 
+use std::collections::HashMap;
+use std::fmt;
 use std::fmt::Debug;
 
 use rustproof_libsmt::backends::smtlib2::*;
 use rustproof_libsmt::backends::backend::*;
 use rustproof_libsmt::backends::z3;
 use rustproof_libsmt::theories::{bitvec, core};
-use rustproof_libsmt::logics::qf_abv::*;
 use petgraph::graph::NodeIndex;
 
 use expression::*;
 
+// The largest range a quantifier without a skolem constant may be expanded over
+const MAX_QUANTIFIER_EXPANSION: i64 = 256;
+
+/// Invokes Z3 to check the satisfiability of the verification conditions of a function.
+///
+/// # Arguments:
+/// * `vcs` - The verification conditions as Expressions, each with a label naming the clause,
+///   specification case, or check it is for.
+/// * `name` - The name of the function whose verification conditions are being checked.
+/// * `debug` - A flag to enable/disable debug printing.
+/// * `report_valid` - Whether to report the function if all its verification conditions are
+///   valid. Problems are always reported.
+/// * `predicates` - The predicates the verification conditions may call, by name.
+///
+/// # Remarks:
+/// * The function is reported valid only if every verification condition is valid.
+/// * If there is more than one verification condition, the result of each is reported, and each
+///   one that could not be proved is given with a counterexample.
+///
+pub fn gen_smtlib (vcs: &[(String, Expression)],
+                   name: String,
+                   debug: bool,
+                   report_valid: bool,
+                   predicates: &HashMap<String, Predicate>) {
+    let mut results: Vec<(&String, Validity)> = Vec::new();
+    for &(ref label, ref vc) in vcs.iter() {
+        match check_validity(vc, debug, predicates) {
+            Validity::Error(error) => {
+                println!("\nfn {}(..)\tError in Verification Condition Generation.\n{}\n",
+                         name,
+                         error);
+                return;
+            },
+            validity => results.push((label, validity)),
+        }
+    }
+
+    if results.iter().all(|&(_, ref v)| *v == Validity::Valid) {
+        if !report_valid {
+            return;
+        }
+        println!("\nfn {}(..)\tVerification Condition is valid.\n", name);
+    } else {
+        println!("\nfn {}(..)\tVerification Condition is not valid.\n", name);
+    }
+    if results.len() > 1 {
+        for &(label, ref validity) in results.iter() {
+            match *validity {
+                Validity::Valid => println!("\t{}: valid", label),
+                _ => println!("\t{}: not valid", label),
+            }
+        }
+        println!("");
+    }
+    for &(label, ref validity) in results.iter() {
+        if let Validity::Invalid(ref model) = *validity {
+            println!("Could not prove {}\n\n{}\n", label, model);
+        }
+    }
+}
+
+/// Checks a verification condition without reporting it.
+///
+/// # Arguments:
+/// * `vc` - A verification condition as an Expression.
+/// * `debug` - A flag to enable/disable debug printing.
+/// * `predicates` - The predicates the verification condition may call, by name.
+///
+/// # Return Value:
+/// * Returns true if the verification condition is valid.
+///
+pub fn is_valid(vc: &Expression, debug: bool, predicates: &HashMap<String, Predicate>) -> bool {
+    check_validity(vc, debug, predicates) == Validity::Valid
+}
+
+// The outcome of checking one verification condition
+#[derive(PartialEq)]
+enum Validity {
+    Valid,
+    // Holds a counterexample
+    Invalid(String),
+    Error(String),
+}
+
 /// Invokes Z3 to check the satisfiability of a verification condition.
 ///
 /// # Arguments:
 /// * `vc` - A verification condition as an Expression.
-/// * `name` - The name of the function whose verification condition is being checked.
 /// * `debug` - A flag to enable/disable debug printing.
+/// * `predicates` - The predicates the verification condition may call, by name.
 ///
 /// # Remarks:
 /// * Simply satisfying P->WP isn't enough; that will only tell us if P->WP is _sometimes true_. We
 /// * need to verify that !(P->WP) is *unsatisfiable* to determine that P->WP is _always true_.
+/// * Quantifiers are removed by `remove_quantifiers` where they can be. The logic is
+///   quantifier-free unless some are left.
+/// * No logic is set for a verification condition with floats, leaving Z3 to combine the
+///   floating-point theory with the others.
+/// * Calls to predicates are unfolded into the predicates' bodies by `unfold_predicates` before
+///   that.
 ///
-pub fn gen_smtlib (vc: &Expression, name: String, debug: bool) {
+fn check_validity(vc: &Expression, debug: bool, predicates: &HashMap<String, Predicate>)
+                  -> Validity {
     // Define an instance of Z3
     let mut z3: z3::Z3 = Default::default();
 
+    // The verification condition is asserted negated
+    let vc = unfold_predicates(vc, predicates);
+    let vc = remove_quantifiers(&vc, Some(false), &mut 0);
+
     // Declare a logic to use
-    let mut solver = SMTLib2::new(Some(QF_ABV));
+    let logic = if uses_floats(&vc) {
+        None
+    } else {
+        Some(VcLogic { quantified: has_quantifier(&vc) })
+    };
+    let mut solver = SMTLib2::new(logic);
 
     // Check the satisfiability of the solver
-    let vcon = solver.expr2smtlib(vc);
+    let vcon = solver.expr2smtlib(&vc);
     let _ = solver.assert(core::OpCodes::Not, &[vcon]);
 
     let (_, check) = solver.solve(&mut z3, debug);
     match check {
-        SMTRes::Sat(_, ref model) => {
-            println!(
-                "\nfn {}(..)\tVerification Condition is not valid.\n\n{}\n",
-                name,
-                model.clone().unwrap()
-            );
-        },
-        SMTRes::Unsat(..) => {
-            println!("\nfn {}(..)\tVerification Condition is valid.\n", name);
-        },
-        SMTRes::Error(ref error, _) => {
-            println!("\nfn {}(..)\tError in Verification Condition Generation.\n{}\n", name, error);
+        SMTRes::Sat(_, ref model) => Validity::Invalid(format!("{}", model.clone().unwrap())),
+        SMTRes::Unsat(..) => Validity::Valid,
+        SMTRes::Error(ref error, _) => Validity::Error(format!("{}", error)),
+    }
+}
+
+/// Replaces each call to a predicate with the predicate's body.
+///
+/// # Arguments:
+/// * `e` - The Expression to unfold calls in.
+/// * `predicates` - The predicates that may be called, by name.
+///
+/// # Return Value:
+/// * Returns the Expression, which calls only built-in functions.
+///
+fn unfold_predicates(e: &Expression, predicates: &HashMap<String, Predicate>) -> Expression {
+    match *e {
+        Expression::BinaryExpression(ref b) => {
+            Expression::BinaryExpression( BinaryExpressionData {
+                op: b.op.clone(),
+                left: Box::new(unfold_predicates(&*b.left, predicates)),
+                right: Box::new(unfold_predicates(&*b.right, predicates)),
+            })
+        },
+        Expression::UnaryExpression(ref u) => {
+            Expression::UnaryExpression( UnaryExpressionData {
+                op: u.op.clone(),
+                e: Box::new(unfold_predicates(&*u.e, predicates)),
+            })
+        },
+        Expression::Quantifier(ref q) => {
+            Expression::Quantifier( QuantifierData {
+                quantifier: q.quantifier.clone(),
+                var: q.var.clone(),
+                lower: q.lower.as_ref().map(|l| Box::new(unfold_predicates(&**l, predicates))),
+                upper: q.upper.as_ref().map(|u| Box::new(unfold_predicates(&**u, predicates))),
+                body: Box::new(unfold_predicates(&*q.body, predicates)),
+            })
+        },
+        Expression::Conditional(ref c) => {
+            Expression::Conditional( ConditionalData {
+                condition: Box::new(unfold_predicates(&*c.condition, predicates)),
+                then_expression: Box::new(unfold_predicates(&*c.then_expression, predicates)),
+                else_expression: Box::new(unfold_predicates(&*c.else_expression, predicates)),
+            })
+        },
+        Expression::Let(ref l) => {
+            Expression::Let( LetData {
+                var: l.var.clone(),
+                value: Box::new(unfold_predicates(&*l.value, predicates)),
+                body: Box::new(unfold_predicates(&*l.body, predicates)),
+            })
+        },
+        Expression::Cast(ref c) => {
+            Expression::Cast( CastData {
+                e: Box::new(unfold_predicates(&*c.e, predicates)),
+                ty: c.ty.clone(),
+            })
+        },
+        Expression::FunctionCall(ref c) => {
+            let args: Vec<Expression> = c.args.iter()
+                                              .map(|a| unfold_predicates(a, predicates))
+                                              .collect();
+            if c.params.is_none() {
+                return Expression::FunctionCall( FunctionCallData {
+                    name: c.name.clone(),
+                    args: args,
+                    params: None,
+                });
+            }
+            let predicate = &predicates[&c.name];
+            let mut body = predicate.body.clone();
+            // Parameters are renamed first, so that an argument naming another parameter is not
+            // substituted again
+            let mut placeholders: Vec<VariableMappingData> = Vec::new();
+            for param in predicate.params.iter() {
+                let placeholder = VariableMappingData {
+                    name: format!("{}!{}", c.name, param.name),
+                    var_type: param.var_type.clone(),
+                };
+                let renamed = Expression::VariableMapping(placeholder.clone());
+                substitute_variable_with_expression(&mut body, param, &renamed);
+                placeholders.push(placeholder);
+            }
+            for (placeholder, arg) in placeholders.iter().zip(args.iter()) {
+                substitute_variable_with_expression(&mut body, placeholder, arg);
+            }
+            body
+        },
+        _ => e.clone(),
+    }
+}
+
+/// Returns an equisatisfiable version of an expression with as few quantifiers as possible.
+///
+/// # Arguments:
+/// * `e` - The Expression to remove quantifiers from.
+/// * `polarity` - `Some(true)` if `e` is asserted, `Some(false)` if its negation is asserted, and
+///                `None` if it appears both ways (e.g. under `EQUIV`).
+/// * `fresh` - A counter used to name skolem constants and bound variables.
+///
+/// # Return Value:
+/// * Returns the Expression, whose remaining quantifiers have no range.
+///
+/// # Remarks:
+/// * An asserted `exists`, or a negated `forall`, is replaced by its body over a fresh skolem
+///   constant.
+/// * A quantifier over a range with constant bounds spanning at most `MAX_QUANTIFIER_EXPANSION`
+///   values is expanded into a conjunction (`forall`) or disjunction (`exists`) over that range.
+/// * Any other quantifier is kept for the solver, over a fresh variable and with its range moved
+///   into its body.
+///
+fn remove_quantifiers(e: &Expression, polarity: Option<bool>, fresh: &mut u32) -> Expression {
+    let flipped = polarity.map(|p| !p);
+    match *e {
+        Expression::BinaryExpression(ref b) => {
+            let (l_polarity, r_polarity) = match b.op {
+                BinaryOperator::And | BinaryOperator::Or => (polarity, polarity),
+                BinaryOperator::Implication => (flipped, polarity),
+                _ => (None, None),
+            };
+            Expression::BinaryExpression( BinaryExpressionData {
+                op: b.op.clone(),
+                left: Box::new(remove_quantifiers(&*b.left, l_polarity, fresh)),
+                right: Box::new(remove_quantifiers(&*b.right, r_polarity, fresh)),
+            })
+        },
+        Expression::UnaryExpression(ref u) => {
+            let e_polarity = match u.op {
+                UnaryOperator::Not => flipped,
+                _ => polarity,
+            };
+            Expression::UnaryExpression( UnaryExpressionData {
+                op: u.op.clone(),
+                e: Box::new(remove_quantifiers(&*u.e, e_polarity, fresh)),
+            })
+        },
+        Expression::Quantifier(ref q) => {
+            let skolemize = match (q.quantifier.clone(), polarity) {
+                (Quantifier::Exists, Some(true)) | (Quantifier::Forall, Some(false)) => true,
+                _ => false,
+            };
+
+            if skolemize {
+                let mut body = guarded_body(q);
+                let skolem = Expression::VariableMapping( VariableMappingData {
+                    name: format!("{}!sk{}", q.var.name, *fresh),
+                    var_type: q.var.var_type.clone(),
+                });
+                *fresh += 1;
+                substitute_variable_with_expression(&mut body, &q.var, &skolem);
+                return remove_quantifiers(&body, polarity, fresh);
+            }
+
+            // Otherwise, expand the quantifier if its range is small and constant
+            let bounds = match (q.lower.as_ref(), q.upper.as_ref()) {
+                (Some(l), Some(u)) => (literal_value(&**l), literal_value(&**u)),
+                _ => (None, None),
+            };
+            let (lower, upper) = match bounds {
+                // The subtraction overflows for ranges far wider than any that is expanded
+                (Some(l), Some(u)) if u.checked_sub(l).map_or(false, |n| {
+                    n <= MAX_QUANTIFIER_EXPANSION
+                }) => (l, u),
+                _ => {
+                    // Otherwise, keep the quantifier over a fresh variable
+                    let bound = VariableMappingData {
+                        name: format!("{}!q{}", q.var.name, *fresh),
+                        var_type: q.var.var_type.clone(),
+                    };
+                    *fresh += 1;
+                    let mut body = guarded_body(q);
+                    let var = Expression::VariableMapping(bound.clone());
+                    substitute_variable_with_expression(&mut body, &q.var, &var);
+                    return Expression::Quantifier( QuantifierData {
+                        quantifier: q.quantifier.clone(),
+                        var: bound,
+                        lower: None,
+                        upper: None,
+                        // A skolem constant cannot depend on the bound variable
+                        body: Box::new(remove_quantifiers(&body, None, fresh)),
+                    });
+                },
+            };
+            let mut expansion = Expression::BooleanLiteral(q.quantifier == Quantifier::Forall);
+            for value in lower..upper {
+                let mut instance = *q.body.clone();
+                let literal = match typed_integer_literal(&value.to_string(), &q.var.var_type) {
+                    Ok(l) => l,
+                    Err(s) => rp_error!("{}", s),
+                };
+                substitute_variable_with_expression(&mut instance, &q.var, &literal);
+                expansion = Expression::BinaryExpression( BinaryExpressionData {
+                    op: match q.quantifier {
+                        Quantifier::Forall => BinaryOperator::And,
+                        Quantifier::Exists => BinaryOperator::Or,
+                    },
+                    left: Box::new(expansion),
+                    right: Box::new(instance),
+                });
+            }
+            remove_quantifiers(&expansion, polarity, fresh)
+        },
+        Expression::Conditional(ref c) => {
+            Expression::Conditional( ConditionalData {
+                condition: Box::new(remove_quantifiers(&*c.condition, None, fresh)),
+                then_expression: Box::new(remove_quantifiers(&*c.then_expression, polarity, fresh)),
+                else_expression: Box::new(remove_quantifiers(&*c.else_expression, polarity, fresh)),
+            })
+        },
+        Expression::FunctionCall(ref c) => {
+            Expression::FunctionCall( FunctionCallData {
+                name: c.name.clone(),
+                args: c.args.iter().map(|a| remove_quantifiers(a, None, fresh)).collect(),
+                params: c.params.clone(),
+            })
+        },
+        Expression::Let(ref l) => {
+            Expression::Let( LetData {
+                var: l.var.clone(),
+                value: Box::new(remove_quantifiers(&*l.value, None, fresh)),
+                body: Box::new(remove_quantifiers(&*l.body, polarity, fresh)),
+            })
+        },
+        Expression::Cast(ref c) => {
+            Expression::Cast( CastData {
+                e: Box::new(remove_quantifiers(&*c.e, None, fresh)),
+                ty: c.ty.clone(),
+            })
+        },
+        _ => e.clone(),
+    }
+}
+
+// Returns the body of a quantifier, restricted to its range
+fn guarded_body(q: &QuantifierData) -> Expression {
+    let var = Expression::VariableMapping(q.var.clone());
+    let mut body = *q.body.clone();
+    // A char ranges over the Unicode scalar values only
+    if q.var.var_type == Types::Char {
+        body = Expression::BinaryExpression( BinaryExpressionData {
+            op: match q.quantifier {
+                Quantifier::Forall => BinaryOperator::Implication,
+                Quantifier::Exists => BinaryOperator::And,
+            },
+            left: Box::new(valid_char(&var)),
+            right: Box::new(body),
+        });
+    }
+    // lower <= var && var < upper
+    if let (Some(l), Some(u)) = (q.lower.as_ref(), q.upper.as_ref()) {
+        let guard = Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::And,
+            left: Box::new(Expression::BinaryExpression( BinaryExpressionData {
+                op: BinaryOperator::LessThanOrEqual,
+                left: l.clone(),
+                right: Box::new(var.clone()),
+            })),
+            right: Box::new(Expression::BinaryExpression( BinaryExpressionData {
+                op: BinaryOperator::LessThan,
+                left: Box::new(var.clone()),
+                right: u.clone(),
+            })),
+        });
+        body = Expression::BinaryExpression( BinaryExpressionData {
+            op: match q.quantifier {
+                Quantifier::Forall => BinaryOperator::Implication,
+                Quantifier::Exists => BinaryOperator::And,
+            },
+            left: Box::new(guard),
+            right: Box::new(body),
+        });
+    }
+    body
+}
+
+// Checks whether an expression has a quantifier left in it
+fn has_quantifier(e: &Expression) -> bool {
+    any_subexpression(e, &|e: &Expression| {
+        match *e {
+            Expression::Quantifier(_) => true,
+            _ => false,
+        }
+    })
+}
+
+// Checks whether an expression has a floating-point value in it
+fn uses_floats(e: &Expression) -> bool {
+    any_subexpression(e, &|e: &Expression| {
+        match *e {
+            Expression::VariableMapping(ref v) => is_float_type(v.var_type.clone()),
+            Expression::FloatLiteral(_) => true,
+            Expression::Cast(ref c) => is_float_type(c.ty.clone()),
+            _ => false,
         }
+    })
+}
+
+// Checks whether an expression, or any expression in it, passes a test
+fn any_subexpression(e: &Expression, test: &Fn(&Expression) -> bool) -> bool {
+    if test(e) {
+        return true;
+    }
+    match *e {
+        Expression::BinaryExpression(ref b) => {
+            any_subexpression(&*b.left, test) || any_subexpression(&*b.right, test)
+        },
+        Expression::UnaryExpression(ref u) => any_subexpression(&*u.e, test),
+        Expression::Quantifier(ref q) => any_subexpression(&*q.body, test),
+        Expression::Conditional(ref c) => {
+            any_subexpression(&*c.condition, test) ||
+            any_subexpression(&*c.then_expression, test) ||
+            any_subexpression(&*c.else_expression, test)
+        },
+        Expression::FunctionCall(ref c) => c.args.iter().any(|a| any_subexpression(a, test)),
+        Expression::Let(ref l) => {
+            any_subexpression(&*l.value, test) || any_subexpression(&*l.body, test)
+        },
+        Expression::Cast(ref c) => any_subexpression(&*c.e, test),
+        _ => false,
     }
+}
 
+// Returns the value of an integer literal, if it fits in an i64
+fn literal_value(e: &Expression) -> Option<i64> {
+    match *e {
+        Expression::SignedBitVector(ref s) => Some(s.value),
+        Expression::UnsignedBitVector(ref u) if u.value <= i64::max_value() as u64 => {
+            Some(u.value as i64)
+        },
+        _ => None,
+    }
+}
+
+/// The sorts of the SMT-LIB FloatingPoint theory, by the widths of their exponent and significand.
+#[derive(Clone, Debug)]
+pub enum FloatSorts {
+    FloatingPoint(u64, u64),
+}
+
+impl fmt::Display for FloatSorts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FloatSorts::FloatingPoint(e, s) => write!(f, "(_ FloatingPoint {} {})", e, s),
+        }
+    }
+}
+
+/// The operations of the SMT-LIB FloatingPoint theory that floats are modelled with.
+#[derive(Clone, Debug)]
+pub enum FloatOps {
+    FreeVar(String),
+    // Rounding to nearest, with ties to even, as Rust rounds
+    RoundNearestEven,
+    // The float with the IEEE 754 bits of a bit-vector, or the float nearest to a signed
+    // bit-vector or to another float, by the widths of the exponent and significand
+    ToFp(u64, u64),
+    // The float nearest to an unsigned bit-vector
+    ToFpUnsigned(u64, u64),
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Neg,
+    Lt,
+    Leq,
+    Gt,
+    Geq,
+    Eq,
+    IsNaN,
+    IsInfinite,
+}
+
+impl fmt::Display for FloatOps {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            FloatOps::FreeVar(ref s) => s.clone(),
+            FloatOps::RoundNearestEven => "RNE".to_owned(),
+            FloatOps::ToFp(e, s) => format!("(_ to_fp {} {})", e, s),
+            FloatOps::ToFpUnsigned(e, s) => format!("(_ to_fp_unsigned {} {})", e, s),
+            FloatOps::Add => "fp.add".to_owned(),
+            FloatOps::Sub => "fp.sub".to_owned(),
+            FloatOps::Mul => "fp.mul".to_owned(),
+            FloatOps::Div => "fp.div".to_owned(),
+            FloatOps::Neg => "fp.neg".to_owned(),
+            FloatOps::Lt => "fp.lt".to_owned(),
+            FloatOps::Leq => "fp.leq".to_owned(),
+            FloatOps::Gt => "fp.gt".to_owned(),
+            FloatOps::Geq => "fp.geq".to_owned(),
+            FloatOps::Eq => "fp.eq".to_owned(),
+            FloatOps::IsNaN => "fp.isNaN".to_owned(),
+            FloatOps::IsInfinite => "fp.isInfinite".to_owned(),
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl SMTNode for FloatOps {
+    fn is_var(&self) -> bool {
+        match *self {
+            FloatOps::FreeVar(_) => true,
+            _ => false,
+        }
+    }
+
+    fn is_const(&self) -> bool {
+        match *self {
+            FloatOps::RoundNearestEven => true,
+            _ => false,
+        }
+    }
+}
+
+define_sorts_for_logic!(VcSorts,
+                        BV -> bitvec::Sorts,
+                        Core -> core::Sorts,
+                        FP -> FloatSorts);
+
+define_fns_for_logic!(VcFn,
+                      BVOps -> bitvec::OpCodes,
+                      CoreOps -> core::OpCodes,
+                      FPOps -> FloatOps,
+                      Binders -> Binder);
+
+/// The logic of a verification condition without floats: bit-vectors, with quantifiers if it has
+/// any.
+#[derive(Clone, Copy, Debug)]
+pub struct VcLogic {
+    pub quantified: bool,
+}
+
+impl fmt::Display for VcLogic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", if self.quantified { "BV" } else { "QF_ABV" })
+    }
+}
+
+impl Logic for VcLogic {
+    type Fns = VcFn;
+    type Sorts = VcSorts;
+
+    fn free_var<T: AsRef<str>>(name: T, ty: VcSorts) -> VcFn {
+        match ty {
+            VcSorts::BV(_) => bitvec::OpCodes::FreeVar(name.as_ref().to_owned()).into(),
+            VcSorts::Core(_) => core::OpCodes::FreeVar(name.as_ref().to_owned()).into(),
+            VcSorts::FP(_) => FloatOps::FreeVar(name.as_ref().to_owned()).into(),
+        }
+    }
+}
+
+/// A quantifier, binding a variable of the given sort in its only operand.
+#[derive(Clone, Debug)]
+pub enum Binder {
+    Forall(String, VcSorts),
+    Exists(String, VcSorts),
+}
+
+impl fmt::Display for Binder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (quantifier, var, sort) = match *self {
+            Binder::Forall(ref v, ref s) => ("forall", v, s),
+            Binder::Exists(ref v, ref s) => ("exists", v, s),
+        };
+        match *sort {
+            VcSorts::BV(bitvec::Sorts::Bool) => write!(f, "{} (({} Bool))", quantifier, var),
+            _ => write!(f, "{} (({} {}))", quantifier, var, sort),
+        }
+    }
+}
+
+impl SMTNode for Binder {
+    fn is_var(&self) -> bool {
+        false
+    }
+
+    fn is_const(&self) -> bool {
+        false
+    }
 }
 
 pub trait Pred2SMT {
@@ -69,12 +633,40 @@ pub trait Pred2SMT {
     fn expr2smtlib (&mut self, &Expression) -> Self::Idx;
 }
 
-impl Pred2SMT for SMTLib2<QF_ABV> {
+impl Pred2SMT for SMTLib2<VcLogic> {
     type Idx = NodeIndex;
-    type Logic = QF_ABV;
+    type Logic = VcLogic;
 
     fn expr2smtlib (&mut self, vc: &Expression) -> Self::Idx {
         match *vc {
+            // Floating-point operations follow IEEE 754: NaN is unordered, -0.0 == +0.0, and
+            // arithmetic rounds to nearest
+            Expression::BinaryExpression (ref b)
+                if is_float_type(determine_evaluation_type(b.left.as_ref())) => {
+                let l = self.expr2smtlib(b.left.as_ref());
+                let r = self.expr2smtlib(b.right.as_ref());
+                let rounded = match b.op {
+                    BinaryOperator::Addition => FloatOps::Add,
+                    BinaryOperator::Subtraction => FloatOps::Sub,
+                    BinaryOperator::Multiplication => FloatOps::Mul,
+                    BinaryOperator::Division => FloatOps::Div,
+                    BinaryOperator::LessThan => return self.assert(FloatOps::Lt, &[l, r]),
+                    BinaryOperator::LessThanOrEqual => return self.assert(FloatOps::Leq, &[l, r]),
+                    BinaryOperator::GreaterThan => return self.assert(FloatOps::Gt, &[l, r]),
+                    BinaryOperator::GreaterThanOrEqual => {
+                        return self.assert(FloatOps::Geq, &[l, r]);
+                    },
+                    BinaryOperator::Equal => return self.assert(FloatOps::Eq, &[l, r]),
+                    BinaryOperator::NotEqual => {
+                        let equal = self.assert(FloatOps::Eq, &[l, r]);
+                        return self.assert(core::OpCodes::Not, &[equal]);
+                    },
+                    // Rejected by the type checker
+                    _ => unreachable!(),
+                };
+                let rounding = self.new_const(FloatOps::RoundNearestEven);
+                return self.assert(rounded, &[rounding, l, r]);
+            },
             Expression::BinaryExpression (ref b) => {
                 let l = self.expr2smtlib(b.left.as_ref());
                 let r = self.expr2smtlib(b.right.as_ref());
@@ -134,16 +726,35 @@ impl Pred2SMT for SMTLib2<QF_ABV> {
                             return self.assert(bitvec::OpCodes::BvXor, &[l,r]);
                         }
                     },
-                    BinaryOperator::BitwiseLeftShift => {
-                        return self.assert(bitvec::OpCodes::BvShl, &[l,r]);
-                    },
-                    BinaryOperator::BitwiseRightShift => {
-                        // Check for signedness
-                        if is_signed_type(determine_evaluation_type(vc)) {
-                            return self.assert(bitvec::OpCodes::BvAShr, &[l,r]);
-                        } else {
-                            return self.assert(bitvec::OpCodes::BvLShr, &[l,r]);
+                    BinaryOperator::BitwiseLeftShift | BinaryOperator::BitwiseRightShift => {
+                        // The shift amount may be narrower or wider than the value shifted
+                        let arithmetic = b.op == BinaryOperator::BitwiseRightShift
+                                         && is_signed_type(determine_evaluation_type(vc));
+                        let l_size = type_size(determine_evaluation_type(b.left.as_ref()))
+                                     .unwrap() as u64;
+                        let r_size = type_size(determine_evaluation_type(b.right.as_ref()))
+                                     .unwrap() as u64;
+                        if r_size <= l_size {
+                            let amount = if r_size < l_size {
+                                self.assert(bitvec::OpCodes::ZeroExtend(l_size - r_size), &[r])
+                            } else {
+                                r
+                            };
+                            return self.assert(shift_opcode(&b.op, arithmetic), &[l, amount]);
                         }
+                        // A wider amount is truncated only if it is less than the width;
+                        // otherwise every bit is shifted out
+                        let amount = self.assert(bitvec::OpCodes::Extract(l_size - 1, 0), &[r]);
+                        let shifted = self.assert(shift_opcode(&b.op, arithmetic), &[l, amount]);
+                        let width = bv_const!(self, l_size, r_size as usize);
+                        let in_range = self.assert(bitvec::OpCodes::BvULt, &[r, width]);
+                        let shifted_out = if arithmetic {
+                            let last = bv_const!(self, l_size - 1, l_size as usize);
+                            self.assert(bitvec::OpCodes::BvAShr, &[l, last])
+                        } else {
+                            bv_const!(self, 0, l_size as usize)
+                        };
+                        return self.assert(core::OpCodes::ITE, &[in_range, shifted, shifted_out]);
                     },
                     BinaryOperator::LessThan => {
                         if is_signed_type(determine_evaluation_type(b.left.as_ref())) {
@@ -202,6 +813,10 @@ impl Pred2SMT for SMTLib2<QF_ABV> {
                 let n = self.expr2smtlib(u.e.as_ref());
                 match u.op {
                     UnaryOperator::Negation => {
+                        let e_type = determine_evaluation_type(u.e.as_ref());
+                        if is_float_type(e_type) {
+                            return self.assert(FloatOps::Neg, &[n]);
+                        }
                         return self.assert(bitvec::OpCodes::BvNeg, &[n]);
                     },
                     UnaryOperator::BitwiseNot => {
@@ -210,18 +825,12 @@ impl Pred2SMT for SMTLib2<QF_ABV> {
                     UnaryOperator::Not => {
                         return self.assert(core::OpCodes::Not, &[n]);
                     },
+                    // Removed once the weakest precondition reaches the function entry
+                    UnaryOperator::Old => unreachable!(),
                 }
             },
             Expression::VariableMapping (ref v) => {
-                let sort = match v.var_type {
-                    Types::Bool => bitvec::Sorts::Bool,
-                    Types::I8 | Types::U8 => bitvec::Sorts::BitVector(8),
-                    Types::I16 | Types::U16 => bitvec::Sorts::BitVector(16),
-                    Types::I32 | Types::U32 => bitvec::Sorts::BitVector(32),
-                    Types::I64 | Types::U64 => bitvec::Sorts::BitVector(64),
-                    Types::Void => unreachable!(),
-                };
-                return self.new_var(Some(&v.name), sort);
+                return self.new_var(Some(&v.name), variable_sort(&v.var_type));
             },
             Expression::BooleanLiteral (ref b) => {
                 return self.new_const(core::OpCodes::Const(*b));
@@ -229,9 +838,226 @@ impl Pred2SMT for SMTLib2<QF_ABV> {
             Expression::UnsignedBitVector (ref u) => {
                 return bv_const!(self, u.value, u.size as usize);
             },
+            Expression::SignedBitVector (ref s) if s.size > 64 => {
+                // The value is sign-extended from 64 bits
+                let value = bv_const!(self, s.value as u64, 64);
+                return self.assert(bitvec::OpCodes::SignExtend(s.size as u64 - 64), &[value]);
+            },
             Expression::SignedBitVector (ref s) => {
                 return bv_const!(self, s.value as u64, s.size as usize);
-            }
+            },
+            Expression::WideBitVector (ref w) => {
+                let high = bv_const!(self, w.high, 64);
+                let low = bv_const!(self, w.low, 64);
+                return self.assert(bitvec::OpCodes::Concat, &[high, low]);
+            },
+            Expression::CharLiteral (ref c) => {
+                return bv_const!(self, *c as u64, 32);
+            },
+            Expression::FloatLiteral (ref f) => {
+                let (exponent, significand) = float_sort(determine_evaluation_type(vc));
+                let bits = bv_const!(self, f.bits, f.size as usize);
+                return self.assert(FloatOps::ToFp(exponent, significand), &[bits]);
+            },
+            // Literal types are inferred before the verification condition is generated
+            Expression::IntegerLiteral (_) | Expression::PointerSizedLiteral (..) => unreachable!(),
+            // The range of a quantifier is moved into its body by remove_quantifiers. The bound
+            // variable is also declared as a constant, which the quantifier hides.
+            Expression::Quantifier (ref q) => {
+                let body = self.expr2smtlib(q.body.as_ref());
+                let sort = variable_sort(&q.var.var_type);
+                let binder = match q.quantifier {
+                    Quantifier::Forall => Binder::Forall(q.var.name.clone(), sort),
+                    Quantifier::Exists => Binder::Exists(q.var.name.clone(), sort),
+                };
+                return self.assert(binder, &[body]);
+            },
+            Expression::Conditional (ref c) => {
+                let condition = self.expr2smtlib(c.condition.as_ref());
+                let then_expression = self.expr2smtlib(c.then_expression.as_ref());
+                let else_expression = self.expr2smtlib(c.else_expression.as_ref());
+                return self.assert(core::OpCodes::ITE, &[condition, then_expression, else_expression]);
+            },
+            Expression::Let (ref l) => {
+                // The solver interface has no let binder, so the value is substituted into the body
+                let mut body = *l.body.clone();
+                substitute_variable_with_expression(&mut body, &l.var, l.value.as_ref());
+                return self.expr2smtlib(&body);
+            },
+            Expression::Cast (ref c) => {
+                let e_type = determine_evaluation_type(&c.e);
+                let e = self.expr2smtlib(c.e.as_ref());
+                if is_float_type(c.ty.clone()) {
+                    // Integers and floats are rounded to the nearest value of the target type
+                    let (exponent, significand) = float_sort(c.ty.clone());
+                    let to_float = if is_unsigned_type(e_type) {
+                        FloatOps::ToFpUnsigned(exponent, significand)
+                    } else {
+                        FloatOps::ToFp(exponent, significand)
+                    };
+                    let rounding = self.new_const(FloatOps::RoundNearestEven);
+                    return self.assert(to_float, &[rounding, e]);
+                }
+                let t_size = type_size(c.ty.clone()).unwrap() as u64;
+                if e_type == Types::Bool {
+                    // true is 1 and false is 0
+                    let one = bv_const!(self, 1, t_size as usize);
+                    let zero = bv_const!(self, 0, t_size as usize);
+                    return self.assert(core::OpCodes::ITE, &[e, one, zero]);
+                }
+                let e_size = type_size(e_type.clone()).unwrap() as u64;
+                if t_size < e_size {
+                    // Truncation keeps the low bits
+                    return self.assert(bitvec::OpCodes::Extract(t_size - 1, 0), &[e]);
+                } else if t_size > e_size {
+                    // Widening extends by the signedness of the source, not the target
+                    if is_signed_type(e_type) {
+                        return self.assert(bitvec::OpCodes::SignExtend(t_size - e_size), &[e]);
+                    } else {
+                        return self.assert(bitvec::OpCodes::ZeroExtend(t_size - e_size), &[e]);
+                    }
+                }
+                // Same width casts reinterpret the bits
+                return e;
+            },
+            Expression::FunctionCall (ref c)
+                if is_float_type(determine_evaluation_type(&c.args[0])) => {
+                let a = self.expr2smtlib(&c.args[0]);
+                match c.name.as_str() {
+                    "is_nan" => return self.assert(FloatOps::IsNaN, &[a]),
+                    "is_infinite" => return self.assert(FloatOps::IsInfinite, &[a]),
+                    "is_finite" => {
+                        let nan = self.assert(FloatOps::IsNaN, &[a]);
+                        let infinite = self.assert(FloatOps::IsInfinite, &[a]);
+                        let special = self.assert(core::OpCodes::Or, &[nan, infinite]);
+                        return self.assert(core::OpCodes::Not, &[special]);
+                    },
+                    _ => unreachable!(),
+                }
+            },
+            Expression::FunctionCall (ref c) => {
+                let a_type = determine_evaluation_type(&c.args[0]);
+                let size = type_size(a_type.clone()).unwrap() as u64;
+                let a = self.expr2smtlib(&c.args[0]);
+                match c.name.as_str() {
+                    "abs" => {
+                        let zero = bv_const!(self, 0, size as usize);
+                        let negative = self.assert(bitvec::OpCodes::BvSLt, &[a, zero]);
+                        let negated = self.assert(bitvec::OpCodes::BvNeg, &[a]);
+                        return self.assert(core::OpCodes::ITE, &[negative, negated, a]);
+                    },
+                    "min" | "max" => {
+                        let b = self.expr2smtlib(&c.args[1]);
+                        let lt = if is_signed_type(a_type) {
+                            self.assert(bitvec::OpCodes::BvSLt, &[a, b])
+                        } else {
+                            self.assert(bitvec::OpCodes::BvULt, &[a, b])
+                        };
+                        if c.name == "min" {
+                            return self.assert(core::OpCodes::ITE, &[lt, a, b]);
+                        } else {
+                            return self.assert(core::OpCodes::ITE, &[lt, b, a]);
+                        }
+                    },
+                    "pow" => {
+                        let mut exponent = match c.args[1] {
+                            Expression::UnsignedBitVector(ref u) => u.value,
+                            _ => unreachable!(),
+                        };
+                        // Exponentiation by squaring
+                        let mut result = bv_const!(self, 1, size as usize);
+                        let mut base = a;
+                        while exponent > 0 {
+                            if exponent & 1 == 1 {
+                                result = self.assert(bitvec::OpCodes::BvMul, &[result, base]);
+                            }
+                            exponent >>= 1;
+                            if exponent > 0 {
+                                base = self.assert(bitvec::OpCodes::BvMul, &[base, base]);
+                            }
+                        }
+                        return result;
+                    },
+                    "count_ones" => {
+                        // Sum of each bit, widened to a u32
+                        let mut result = bv_const!(self, 0, 32);
+                        for i in 0..size {
+                            let bit = self.assert(bitvec::OpCodes::Extract(i, i), &[a]);
+                            let wide = self.assert(bitvec::OpCodes::ZeroExtend(31), &[bit]);
+                            result = self.assert(bitvec::OpCodes::BvAdd, &[result, wide]);
+                        }
+                        return result;
+                    },
+                    "leading_zeros" | "trailing_zeros" => {
+                        // A chain of ite over the bits; the outermost test is for the bit that
+                        // is counted from
+                        let one = bv_const!(self, 1, 1);
+                        let mut result = bv_const!(self, size, 32);
+                        for j in 0..size {
+                            let (i, zeros) = if c.name == "leading_zeros" {
+                                (j, size - 1 - j)
+                            } else {
+                                (size - 1 - j, size - 1 - j)
+                            };
+                            let bit = self.assert(bitvec::OpCodes::Extract(i, i), &[a]);
+                            let set = self.assert(core::OpCodes::Cmp, &[bit, one]);
+                            let count = bv_const!(self, zeros, 32);
+                            result = self.assert(core::OpCodes::ITE, &[set, count, result]);
+                        }
+                        return result;
+                    },
+                    "is_power_of_two" => {
+                        // x != 0 && (x & (x - 1)) == 0
+                        let zero = bv_const!(self, 0, size as usize);
+                        let one = bv_const!(self, 1, size as usize);
+                        let is_zero = self.assert(core::OpCodes::Cmp, &[a, zero]);
+                        let nonzero = self.assert(core::OpCodes::Not, &[is_zero]);
+                        let pred = self.assert(bitvec::OpCodes::BvSub, &[a, one]);
+                        let masked = self.assert(bitvec::OpCodes::BvAnd, &[a, pred]);
+                        let single = self.assert(core::OpCodes::Cmp, &[masked, zero]);
+                        return self.assert(core::OpCodes::And, &[nonzero, single]);
+                    },
+                    _ => unreachable!(),
+                }
+            },
         }
     }
 }
+
+// Returns the sort of a variable of a type
+fn variable_sort(ty: &Types) -> VcSorts {
+    let size = match *ty {
+        Types::Bool => return bitvec::Sorts::Bool.into(),
+        Types::F32 | Types::F64 => {
+            let (exponent, significand) = float_sort(ty.clone());
+            return FloatSorts::FloatingPoint(exponent, significand).into();
+        },
+        Types::I8 | Types::U8 => 8,
+        Types::I16 | Types::U16 => 16,
+        Types::I32 | Types::U32 => 32,
+        Types::I64 | Types::U64 => 64,
+        Types::I128 | Types::U128 => 128,
+        Types::Char => 32,
+        Types::Isize | Types::Usize => pointer_width() as usize,
+        Types::Void | Types::Unknown => unreachable!(),
+    };
+    bitvec::Sorts::BitVector(size).into()
+}
+
+// Returns the solver operation for a shift
+fn shift_opcode(op: &BinaryOperator, arithmetic: bool) -> bitvec::OpCodes {
+    match *op {
+        BinaryOperator::BitwiseLeftShift => bitvec::OpCodes::BvShl,
+        _ if arithmetic => bitvec::OpCodes::BvAShr,
+        _ => bitvec::OpCodes::BvLShr,
+    }
+}
+
+// Returns the widths of the exponent and significand of a floating-point type, as in IEEE 754
+fn float_sort(t: Types) -> (u64, u64) {
+    match t {
+        Types::F32 => (8, 24),
+        Types::F64 => (11, 53),
+        _ => unreachable!(),
+    }
+}
@@ -89,6 +89,162 @@ fn test_assertion_examples(){
     assert!(test_example_file("test_assert"));
 }
 
+// Test example for type inference examples
+#[test]
+fn test_inference_examples(){
+    assert!(test_example_file("test_inference"));
+}
+
+// Test example for reference argument examples
+#[test]
+fn test_reference_examples(){
+    assert!(test_example_file("test_references"));
+}
+
+// Test example for quantifier examples
+#[test]
+fn test_quantifier_examples(){
+    assert!(test_example_file("test_quantifiers"));
+}
+
+// Test example for if-then-else and let examples
+#[test]
+fn test_ite_examples(){
+    assert!(test_example_file("test_ite"));
+}
+
+// Test example for built-in function examples
+#[test]
+fn test_builtin_examples(){
+    assert!(test_example_file("test_builtins"));
+}
+
+// Test example for predicate examples
+#[test]
+fn test_predicate_examples(){
+    assert!(test_example_file("test_predicates"));
+}
+
+// Test example for requires and ensures examples
+#[test]
+fn test_clause_examples(){
+    assert!(test_example_file("test_clauses"));
+}
+
+// Test example for specification case examples
+#[test]
+fn test_case_examples(){
+    assert!(test_example_file("test_cases"));
+}
+
+// Test example for panic condition examples
+#[test]
+fn test_panic_examples(){
+    assert!(test_example_file("test_panics"));
+}
+
+// Test example for checking unannotated functions
+#[test]
+fn test_check_all_examples(){
+    assert!(test_example_file("test_check_all"));
+}
+
+// Test example for shift examples
+#[test]
+fn test_shift_examples(){
+    assert!(test_example_file("test_shifts"));
+}
+
+// Test example for negation examples
+#[test]
+fn test_negation_examples(){
+    assert!(test_example_file("test_negation"));
+}
+
+// Test example for wrapping arithmetic examples
+#[test]
+fn test_wrapping_examples(){
+    assert!(test_example_file("test_wrapping"));
+}
+
+// Test example for crate-level wrapping arithmetic examples
+#[test]
+fn test_wrapping_crate_examples(){
+    assert!(test_example_file("test_wrapping_crate"));
+}
+
+// Test example for integer method examples
+#[test]
+fn test_integer_method_examples(){
+    assert!(test_example_file("test_integer_methods"));
+}
+
+// Test example for integer cast examples
+#[test]
+fn test_cast_examples(){
+    assert!(test_example_file("test_casts"));
+}
+
+// Test example for isize and usize examples
+#[test]
+fn test_pointer_width_examples(){
+    assert!(test_example_file("test_pointer_width"));
+}
+
+// Test example for char examples
+#[test]
+fn test_char_examples(){
+    assert!(test_example_file("test_chars"));
+}
+
+// Test example for floating-point examples
+#[test]
+fn test_float_examples(){
+    assert!(test_example_file("test_floats"));
+}
+
+// Test example for recursion examples
+#[test]
+fn test_recursion_examples(){
+    assert!(test_example_file("test_recursion"));
+}
+
+// Test example for loop examples
+#[test]
+fn test_loop_examples(){
+    assert!(test_example_file("test_loops"));
+}
+
+// Test example for loop unrolling examples
+#[test]
+fn test_unroll_examples(){
+    assert!(test_example_file("test_unroll"));
+}
+
+// Test example for loop invariant inference examples
+#[test]
+fn test_invariant_inference_examples(){
+    assert!(test_example_file("test_invariant_inference"));
+}
+
+// Test example for template invariant inference examples
+#[test]
+fn test_houdini_examples(){
+    assert!(test_example_file("test_houdini"));
+}
+
+// Test example for k-induction examples
+#[test]
+fn test_k_induction_examples(){
+    assert!(test_example_file("test_k_induction"));
+}
+
+// Test example for equivalence checking examples
+#[test]
+fn test_equivalence_examples(){
+    assert!(test_example_file("test_equivalence"));
+}
+
 // Tests whether the system testing functions are appropriately catching test failure
 #[test]
 #[should_panic]
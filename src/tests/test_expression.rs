@@ -377,4 +377,696 @@ fn check_signedness_u64() {
     assert!(!is_valid_signed(&num));
     assert!(is_valid_unsigned(&var));
     assert!(is_valid_unsigned(&num));
-}
\ No newline at end of file
+}
+
+#[test]
+fn infer_types_from_environment() {
+    let env = vec![ VariableMappingData { name: "x".to_string(), var_type: Types::I32 } ];
+    // x <= 5
+    let mut p: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::LessThanOrEqual,
+        left: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "x".to_string(),
+            var_type: Types::Unknown
+        })),
+        right: Box::new( Expression::IntegerLiteral("5".to_string()) ),
+    });
+
+    let correct_result: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::LessThanOrEqual,
+        left: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "x".to_string(),
+            var_type: Types::I32
+        })),
+        right: Box::new( Expression::SignedBitVector( SignedBitVectorData {
+            size: 32u8,
+            value: 5i64,
+        })),
+    });
+    assert!(infer_types(&mut p, &env).is_ok());
+    assert_eq!(p, correct_result);
+}
+
+#[test]
+fn infer_types_literal_on_left() {
+    let env = vec![ VariableMappingData { name: "y".to_string(), var_type: Types::U8 } ];
+    // 255 - y == 0
+    let mut p: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::Equal,
+        left: Box::new( Expression::BinaryExpression( BinaryExpressionData{
+            op: BinaryOperator::Subtraction,
+            left: Box::new( Expression::IntegerLiteral("255".to_string()) ),
+            right: Box::new( Expression::VariableMapping( VariableMappingData {
+                name: "y".to_string(),
+                var_type: Types::Unknown
+            })),
+        })),
+        right: Box::new( Expression::IntegerLiteral("0".to_string()) ),
+    });
+    assert!(infer_types(&mut p, &env).is_ok());
+    assert!(ty_check(&p).is_ok());
+}
+
+#[test]
+fn infer_types_conflicts() {
+    let env = vec![ VariableMappingData { name: "x".to_string(), var_type: Types::U8 } ];
+    // x: i32 > 0
+    let mut annotated: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::GreaterThan,
+        left: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "x".to_string(),
+            var_type: Types::I32
+        })),
+        right: Box::new( Expression::IntegerLiteral("0".to_string()) ),
+    });
+    // x > 256
+    let mut out_of_range: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::GreaterThan,
+        left: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "x".to_string(),
+            var_type: Types::Unknown
+        })),
+        right: Box::new( Expression::IntegerLiteral("256".to_string()) ),
+    });
+    // 5 < 6
+    let mut ambiguous: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::LessThan,
+        left: Box::new( Expression::IntegerLiteral("5".to_string()) ),
+        right: Box::new( Expression::IntegerLiteral("6".to_string()) ),
+    });
+    // z > 0
+    let mut unknown: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::GreaterThan,
+        left: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "z".to_string(),
+            var_type: Types::Unknown
+        })),
+        right: Box::new( Expression::IntegerLiteral("0".to_string()) ),
+    });
+
+    assert!(infer_types(&mut annotated, &env).is_err());
+    assert!(infer_types(&mut out_of_range, &env).is_err());
+    assert!(infer_types(&mut ambiguous, &env).is_err());
+    assert!(infer_types(&mut unknown, &env).is_err());
+}
+
+#[test]
+fn substitute_skips_old() {
+    let target_var : VariableMappingData = VariableMappingData {
+        name: "x".to_string(),
+        var_type: Types::I32
+    };
+    let target: Expression = Expression::VariableMapping( target_var.clone() );
+    let replacement: Expression = Expression::VariableMapping( VariableMappingData {
+        name: "y".to_string(),
+        var_type: Types::I32
+    });
+    let old: Expression = Expression::UnaryExpression( UnaryExpressionData{
+        op: UnaryOperator::Old,
+        e: Box::new( target.clone() ),
+    });
+    // x == old(x)
+    let mut p: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::Equal,
+        left: Box::new( target.clone() ),
+        right: Box::new( old.clone() ),
+    });
+
+    let correct_result: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::Equal,
+        left: Box::new( replacement.clone() ),
+        right: Box::new( old.clone() ),
+    });
+    // An assignment does not change the entry state
+    let mut q = p.clone();
+    substitute_state_variable(&mut p, &target_var, &replacement);
+    assert_eq!(p, correct_result);
+    assert!(contains_old(&p));
+
+    // Other substitutions replace the variable within old() as well
+    substitute_variable_with_expression(&mut q, &target_var, &replacement);
+    assert_eq!(q, Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::Equal,
+        left: Box::new( replacement.clone() ),
+        right: Box::new( Expression::UnaryExpression( UnaryExpressionData{
+            op: UnaryOperator::Old,
+            e: Box::new( replacement.clone() ),
+        })),
+    }));
+
+    let stripped: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::Equal,
+        left: Box::new( replacement.clone() ),
+        right: Box::new( target.clone() ),
+    });
+    remove_old(&mut p);
+    assert_eq!(p, stripped);
+    assert!(!contains_old(&p));
+}
+
+#[test]
+fn substitute_quantifier_scoping() {
+    let i_var : VariableMappingData = VariableMappingData {
+        name: "i".to_string(),
+        var_type: Types::U32
+    };
+    let x_var : VariableMappingData = VariableMappingData {
+        name: "x".to_string(),
+        var_type: Types::U32
+    };
+    // forall i: u32, i < x
+    let quantifier: Expression = Expression::Quantifier( QuantifierData {
+        quantifier: Quantifier::Forall,
+        var: i_var.clone(),
+        lower: None,
+        upper: None,
+        body: Box::new( Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::LessThan,
+            left: Box::new( Expression::VariableMapping(i_var.clone()) ),
+            right: Box::new( Expression::VariableMapping(x_var.clone()) ),
+        })),
+    });
+
+    // The bound variable shadows the target
+    let mut shadowed = quantifier.clone();
+    let replacement: Expression = Expression::VariableMapping( VariableMappingData {
+        name: "y".to_string(),
+        var_type: Types::U32
+    });
+    substitute_variable_with_expression(&mut shadowed, &i_var, &replacement);
+    assert_eq!(shadowed, quantifier);
+
+    // Replacing x with i must not capture the replacement
+    let mut captured = quantifier.clone();
+    substitute_variable_with_expression(&mut captured,
+                                        &x_var,
+                                        &Expression::VariableMapping(i_var.clone()));
+    match captured {
+        Expression::Quantifier(ref q) => {
+            assert!(q.var.name != "i");
+            assert!(occurs_free("i", &*q.body));
+            assert!(occurs_free(&q.var.name, &*q.body));
+        },
+        _ => panic!("quantifier was replaced"),
+    }
+    assert!(occurs_free("i", &captured));
+
+    // forall i: u32, old(i) < x
+    let mut in_old: Expression = Expression::Quantifier( QuantifierData {
+        quantifier: Quantifier::Forall,
+        var: i_var.clone(),
+        lower: None,
+        upper: None,
+        body: Box::new( Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::LessThan,
+            left: Box::new( Expression::UnaryExpression( UnaryExpressionData {
+                op: UnaryOperator::Old,
+                e: Box::new( Expression::VariableMapping(i_var.clone()) ),
+            })),
+            right: Box::new( Expression::VariableMapping(x_var.clone()) ),
+        })),
+    });
+    // The bound variable is renamed within old() too, even by an assignment
+    substitute_state_variable(&mut in_old, &x_var, &Expression::VariableMapping(i_var.clone()));
+    match in_old {
+        Expression::Quantifier(ref q) => {
+            assert!(q.var.name != "i");
+            match *q.body {
+                Expression::BinaryExpression(ref b) => {
+                    assert_eq!(*b.left, Expression::UnaryExpression( UnaryExpressionData {
+                        op: UnaryOperator::Old,
+                        e: Box::new( Expression::VariableMapping(q.var.clone()) ),
+                    }));
+                    assert_eq!(*b.right, Expression::VariableMapping(i_var.clone()));
+                },
+                _ => panic!("body was replaced"),
+            }
+        },
+        _ => panic!("quantifier was replaced"),
+    }
+}
+
+#[test]
+fn conditional_and_let_types() {
+    let x_var : VariableMappingData = VariableMappingData {
+        name: "x".to_string(),
+        var_type: Types::I32
+    };
+    let v_var : VariableMappingData = VariableMappingData {
+        name: "v".to_string(),
+        var_type: Types::Unknown
+    };
+    // if x > 0 then x else -x
+    let mut conditional: Expression = Expression::Conditional( ConditionalData {
+        condition: Box::new( Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::GreaterThan,
+            left: Box::new( Expression::VariableMapping(x_var.clone()) ),
+            right: Box::new( Expression::IntegerLiteral("0".to_string()) ),
+        })),
+        then_expression: Box::new( Expression::VariableMapping(x_var.clone()) ),
+        else_expression: Box::new( Expression::UnaryExpression( UnaryExpressionData {
+            op: UnaryOperator::Negation,
+            e: Box::new( Expression::VariableMapping(x_var.clone()) ),
+        })),
+    });
+    assert_eq!(infer_types(&mut conditional, &[]), Ok(()));
+    assert_eq!(ty_check(&conditional), Ok(true));
+    assert_eq!(determine_evaluation_type(&conditional), Types::I32);
+
+    // let v = 1 in v == x
+    let mut binding: Expression = Expression::Let( LetData {
+        var: v_var.clone(),
+        value: Box::new( Expression::IntegerLiteral("1".to_string()) ),
+        body: Box::new( Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::Equal,
+            left: Box::new( Expression::VariableMapping(v_var.clone()) ),
+            right: Box::new( Expression::VariableMapping(x_var.clone()) ),
+        })),
+    });
+    // The value alone does not determine the type of v
+    assert!(infer_types(&mut binding.clone(), &[]).is_err());
+
+    // Mismatched branches are rejected
+    let mismatched: Expression = Expression::Conditional( ConditionalData {
+        condition: Box::new( Expression::BooleanLiteral(true) ),
+        then_expression: Box::new( Expression::VariableMapping(x_var.clone()) ),
+        else_expression: Box::new( Expression::BooleanLiteral(false) ),
+    });
+    assert!(ty_check(&mismatched).is_err());
+
+    // let v: i32 = 1 in v == x
+    if let Expression::Let(ref mut l) = binding {
+        l.var.var_type = Types::I32;
+    }
+    assert_eq!(infer_types(&mut binding, &[]), Ok(()));
+    assert_eq!(ty_check(&binding), Ok(true));
+
+    // Substituting x with v renames the bound variable
+    substitute_variable_with_expression(&mut binding,
+                                        &x_var,
+                                        &Expression::VariableMapping(VariableMappingData {
+                                            name: "v".to_string(),
+                                            var_type: Types::I32
+                                        }));
+    match binding {
+        Expression::Let(ref l) => {
+            assert!(l.var.name != "v");
+            assert!(occurs_free("v", &*l.body));
+        },
+        _ => panic!("let was replaced"),
+    }
+}
+
+#[test]
+fn builtin_function_types() {
+    let x_var : VariableMappingData = VariableMappingData {
+        name: "x".to_string(),
+        var_type: Types::U8
+    };
+    let x: Expression = Expression::VariableMapping(x_var.clone());
+
+    // max(x, 3)
+    let max: Expression = Expression::FunctionCall( FunctionCallData {
+        name: "max".to_string(),
+        args: vec![x.clone(), Expression::IntegerLiteral("3".to_string())],
+        params: None,
+    });
+    let mut max_eq: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::Equal,
+        left: Box::new( max.clone() ),
+        right: Box::new( x.clone() ),
+    });
+    assert_eq!(infer_types(&mut max_eq, &[]), Ok(()));
+    assert_eq!(ty_check(&max_eq), Ok(true));
+
+    // count_ones(x) == 3
+    let mut count: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::Equal,
+        left: Box::new( Expression::FunctionCall( FunctionCallData {
+            name: "count_ones".to_string(),
+            args: vec![x.clone()],
+            params: None,
+        })),
+        right: Box::new( Expression::IntegerLiteral("3".to_string()) ),
+    });
+    assert_eq!(infer_types(&mut count, &[]), Ok(()));
+    assert_eq!(ty_check(&count), Ok(true));
+    match count {
+        Expression::BinaryExpression(ref b) => {
+            assert_eq!(determine_evaluation_type(&*b.left), Types::U32);
+        },
+        _ => panic!("comparison was replaced"),
+    }
+
+    // abs requires a signed argument
+    let abs: Expression = Expression::FunctionCall( FunctionCallData {
+        name: "abs".to_string(),
+        args: vec![x.clone()],
+        params: None,
+    });
+    assert!(ty_check(&abs).is_err());
+
+    // pow requires a constant exponent
+    let pow: Expression = Expression::FunctionCall( FunctionCallData {
+        name: "pow".to_string(),
+        args: vec![x.clone(), Expression::VariableMapping( VariableMappingData {
+            name: "n".to_string(),
+            var_type: Types::U32
+        })],
+        params: None,
+    });
+    assert!(ty_check(&pow).is_err());
+
+    // Unknown functions and wrong arities are rejected
+    let mut unknown: Expression = Expression::FunctionCall( FunctionCallData {
+        name: "sqrt".to_string(),
+        args: vec![x.clone()],
+        params: None,
+    });
+    assert!(infer_types(&mut unknown, &[]).is_err());
+    let arity: Expression = Expression::FunctionCall( FunctionCallData {
+        name: "min".to_string(),
+        args: vec![x.clone()],
+        params: None,
+    });
+    assert!(ty_check(&arity).is_err());
+}
+
+#[test]
+fn mixed_width_shift_types() {
+    // x: u64 << y: i8
+    let shift: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::BitwiseLeftShift,
+        left: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "x".to_string(),
+            var_type: Types::U64
+        })),
+        right: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "y".to_string(),
+            var_type: Types::I8
+        })),
+    });
+    assert_eq!(ty_check(&shift), Ok(true));
+    assert_eq!(determine_evaluation_type(&shift), Types::U64);
+
+    // x: u64 << true
+    let bad_shift: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::BitwiseLeftShift,
+        left: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "x".to_string(),
+            var_type: Types::U64
+        })),
+        right: Box::new( Expression::BooleanLiteral(true) ),
+    });
+    assert!(ty_check(&bad_shift).is_err());
+}
+
+#[test]
+fn cast_types() {
+    // x: i8 as u64
+    let cast: Expression = Expression::Cast( CastData {
+        e: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "x".to_string(),
+            var_type: Types::I8
+        })),
+        ty: Types::U64
+    });
+    assert_eq!(ty_check(&cast), Ok(true));
+    assert_eq!(determine_evaluation_type(&cast), Types::U64);
+
+    // true as u8
+    let bool_cast: Expression = Expression::Cast( CastData {
+        e: Box::new( Expression::BooleanLiteral(true) ),
+        ty: Types::U8
+    });
+    assert_eq!(ty_check(&bool_cast), Ok(true));
+
+    // x: u8 as bool
+    let bad_cast: Expression = Expression::Cast( CastData {
+        e: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "x".to_string(),
+            var_type: Types::U8
+        })),
+        ty: Types::Bool
+    });
+    assert!(ty_check(&bad_cast).is_err());
+
+    // 300 as u8 takes the target type, and so is out of range
+    let mut literal_cast: Expression = Expression::Cast( CastData {
+        e: Box::new( Expression::IntegerLiteral("300".to_string()) ),
+        ty: Types::U8
+    });
+    assert!(infer_types(&mut literal_cast, &[]).is_err());
+}
+
+#[test]
+fn pointer_sized_types() {
+    set_pointer_width(32);
+    assert_eq!(type_size(Types::Usize), Some(32));
+    assert_eq!(concrete_type(Types::Isize), Types::I32);
+    assert_eq!(determine_evaluation_type(&max_value(&Types::Usize)), Types::U32);
+    assert!(typed_integer_literal("4294967295", &Types::Usize).is_ok());
+    assert!(typed_integer_literal("4294967296", &Types::Usize).is_err());
+
+    set_pointer_width(64);
+    assert_eq!(concrete_type(Types::Usize), Types::U64);
+    assert!(typed_integer_literal("4294967296", &Types::Usize).is_ok());
+
+    // x: usize + 1usize
+    let mut p: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::Addition,
+        left: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "x".to_string(),
+            var_type: Types::Usize
+        })),
+        right: Box::new( Expression::IntegerLiteral("1".to_string()) ),
+    });
+    assert!(infer_types(&mut p, &[]).is_ok());
+    assert_eq!(ty_check(&p), Ok(true));
+    assert_eq!(determine_evaluation_type(&p), Types::U64);
+}
+
+#[test]
+fn pointer_sized_literals() {
+    // x: usize < 4294967296usize
+    let p: Expression = Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::LessThan,
+        left: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "x".to_string(),
+            var_type: Types::Usize
+        })),
+        right: Box::new( Expression::PointerSizedLiteral("4294967296".to_string(),
+                                                         Types::Usize) ),
+    });
+
+    // The literal is sized once the pointer width is known
+    set_pointer_width(32);
+    assert_eq!(infer_types(&mut p.clone(), &[]),
+               Err("Out of range value for 32-bit usize type: 4294967296".to_string()));
+
+    set_pointer_width(64);
+    let mut q = p.clone();
+    assert!(infer_types(&mut q, &[]).is_ok());
+    assert_eq!(ty_check(&q), Ok(true));
+    assert_eq!(determine_evaluation_type(&q), Types::Bool);
+}
+
+#[test]
+fn wide_integer_literals() {
+    // Literals that fit in 64 bits are stored as such
+    let small = typed_integer_literal("-5", &Types::I128).unwrap();
+    assert!(small == Expression::SignedBitVector( SignedBitVectorData { size: 128, value: -5 } ));
+    assert_eq!(determine_evaluation_type(&small), Types::I128);
+    assert_eq!(ty_check(&small), Ok(true));
+
+    let max = typed_integer_literal("340282366920938463463374607431768211455", &Types::U128);
+    assert!(max.unwrap() == max_value(&Types::U128));
+    assert!(typed_integer_literal("340282366920938463463374607431768211456", &Types::U128)
+            .is_err());
+    assert!(typed_integer_literal("-1", &Types::U128).is_err());
+
+    let min = typed_integer_literal("-170141183460469231731687303715884105728", &Types::I128);
+    assert!(min.unwrap() == min_value(&Types::I128));
+    assert!(typed_integer_literal("170141183460469231731687303715884105728", &Types::I128)
+            .is_err());
+    assert_eq!(format!("{}", min_value(&Types::I128)),
+               "(-170141183460469231731687303715884105728i128)");
+}
+
+#[test]
+fn char_types() {
+    let c = Expression::VariableMapping( VariableMappingData {
+        name: "c".to_string(),
+        var_type: Types::Char
+    });
+    // c == 'a'
+    let equal: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::Equal,
+        left: Box::new(c.clone()),
+        right: Box::new(Expression::CharLiteral('a')),
+    });
+    assert_eq!(ty_check(&equal), Ok(true));
+    assert_eq!(ty_check(&valid_char(&c)), Ok(true));
+
+    // c + c
+    let sum: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::Addition,
+        left: Box::new(c.clone()),
+        right: Box::new(c.clone()),
+    });
+    assert!(ty_check(&sum).is_err());
+
+    // 65 as char takes u8 for the literal; x: u32 as char is not allowed
+    let mut literal_cast: Expression = Expression::Cast( CastData {
+        e: Box::new( Expression::IntegerLiteral("65".to_string()) ),
+        ty: Types::Char
+    });
+    assert!(infer_types(&mut literal_cast, &[]).is_ok());
+    assert_eq!(ty_check(&literal_cast), Ok(true));
+    let bad_cast: Expression = Expression::Cast( CastData {
+        e: Box::new( Expression::VariableMapping( VariableMappingData {
+            name: "x".to_string(),
+            var_type: Types::U32
+        })),
+        ty: Types::Char
+    });
+    assert!(ty_check(&bad_cast).is_err());
+
+    // Escapes must name a Unicode scalar value
+    assert!(escaped_char_literal("1F600").unwrap() == Expression::CharLiteral('\u{1F600}'));
+    assert!(escaped_char_literal("D800").is_err());
+    assert!(escaped_char_literal("110000").is_err());
+    assert!(escaped_char_literal("1000000000000").is_err());
+}
+
+#[test]
+fn float_types() {
+    let x = Expression::VariableMapping( VariableMappingData {
+        name: "x".to_string(),
+        var_type: Types::F64
+    });
+    // 1.5f64 is stored as its IEEE 754 bits
+    let one_and_a_half = float_literal("1.5", &Types::F64).unwrap();
+    assert!(one_and_a_half == Expression::FloatLiteral( FloatLiteralData {
+        size: 64,
+        bits: 0x3FF8000000000000
+    }));
+    assert!(float_literal("1.5", &Types::F32).unwrap() == Expression::FloatLiteral(
+        FloatLiteralData { size: 32, bits: 0x3FC00000 }
+    ));
+    assert!(float_literal("1.5", &Types::U32).is_err());
+
+    // x < 1.5f64 and -x == x
+    let less: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::LessThan,
+        left: Box::new(x.clone()),
+        right: Box::new(one_and_a_half.clone()),
+    });
+    assert_eq!(ty_check(&less), Ok(true));
+    let negated: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::Equal,
+        left: Box::new(Expression::UnaryExpression( UnaryExpressionData {
+            op: UnaryOperator::Negation,
+            e: Box::new(x.clone()),
+        })),
+        right: Box::new(x.clone()),
+    });
+    assert_eq!(ty_check(&negated), Ok(true));
+
+    // Arithmetic on floats is allowed, except for the remainder
+    let sum: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::Addition,
+        left: Box::new(x.clone()),
+        right: Box::new(one_and_a_half.clone()),
+    });
+    assert_eq!(ty_check(&sum), Ok(true));
+    assert!(determine_evaluation_type(&sum) == Types::F64);
+    let remainder: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::Modulo,
+        left: Box::new(x.clone()),
+        right: Box::new(one_and_a_half.clone()),
+    });
+    assert!(ty_check(&remainder).is_err());
+    let mixed: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::Addition,
+        left: Box::new(x.clone()),
+        right: Box::new(float_literal("1.5", &Types::F32).unwrap()),
+    });
+    assert!(ty_check(&mixed).is_err());
+
+    // Integers and floats may be cast to floats, but floats not to integers
+    let widened = Expression::Cast( CastData {
+        e: Box::new(Expression::SignedBitVector( SignedBitVectorData { size: 32, value: -3 } )),
+        ty: Types::F64,
+    });
+    assert_eq!(ty_check(&widened), Ok(true));
+    assert!(determine_evaluation_type(&widened) == Types::F64);
+    let narrowed = Expression::Cast( CastData {
+        e: Box::new(x.clone()),
+        ty: Types::F32,
+    });
+    assert_eq!(ty_check(&narrowed), Ok(true));
+    let truncated = Expression::Cast( CastData {
+        e: Box::new(x.clone()),
+        ty: Types::I32,
+    });
+    assert!(ty_check(&truncated).is_err());
+    let from_bool = Expression::Cast( CastData {
+        e: Box::new(Expression::BooleanLiteral(true)),
+        ty: Types::F32,
+    });
+    assert!(ty_check(&from_bool).is_err());
+
+    // Bitwise operations on floats are rejected
+    let not: Expression = Expression::UnaryExpression( UnaryExpressionData {
+        op: UnaryOperator::BitwiseNot,
+        e: Box::new(x.clone()),
+    });
+    assert!(ty_check(&not).is_err());
+
+    // is_nan(x) is a bool; is_nan of an integer is not allowed
+    let nan: Expression = Expression::FunctionCall( FunctionCallData {
+        name: "is_nan".to_string(),
+        args: vec![x.clone()],
+        params: None,
+    });
+    assert_eq!(ty_check(&nan), Ok(true));
+    assert!(determine_evaluation_type(&nan) == Types::Bool);
+    let integer_nan: Expression = Expression::FunctionCall( FunctionCallData {
+        name: "is_nan".to_string(),
+        args: vec![Expression::UnsignedBitVector( UnsignedBitVectorData { size: 8, value: 1 } )],
+        params: None,
+    });
+    assert!(ty_check(&integer_nan).is_err());
+}
+
+#[test]
+fn free_variables_skip_bound_variables() {
+    let i_var = VariableMappingData { name: "i".to_string(), var_type: Types::U32 };
+    let n_var = VariableMappingData { name: "n".to_string(), var_type: Types::U32 };
+    // forall i: u32. i < n
+    let quantifier: Expression = Expression::Quantifier( QuantifierData {
+        quantifier: Quantifier::Forall,
+        var: i_var.clone(),
+        lower: None,
+        upper: None,
+        body: Box::new( Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::LessThan,
+            left: Box::new(Expression::VariableMapping(i_var.clone())),
+            right: Box::new(Expression::VariableMapping(n_var.clone())),
+        })),
+    });
+    assert!(free_variables(&quantifier) == vec![n_var.clone()]);
+
+    // i <= n && forall i: u32. i < n
+    let conjunction: Expression = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::And,
+        left: Box::new( Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::LessThanOrEqual,
+            left: Box::new(Expression::VariableMapping(i_var.clone())),
+            right: Box::new(Expression::VariableMapping(n_var.clone())),
+        })),
+        right: Box::new(quantifier.clone()),
+    });
+    assert!(free_variables(&conjunction) == vec![i_var.clone(), n_var.clone()]);
+}
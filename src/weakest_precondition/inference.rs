@@ -0,0 +1,736 @@
+// The Rust-Proof Project is copyright 2016, Sami Sahli,
+// Michael Salter, Matthew Slocum, Vincent Schuster,
+// Bradley Rasmussen, Drew Gohman, and Matthew O'Brien.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Infers candidate loop invariants by a forward analysis of the integer variables of a function.
+
+use std::collections::HashMap;
+use std::i64;
+
+use super::super::MirData;
+use super::{gen_lvalue, gen_expression, is_reference, successors};
+use expression::*;
+use parser::Arithmetic;
+use rustc::mir::repr::*;
+use rustc_data_structures::indexed_vec::Idx;
+
+/// Infers bounds on the integer variables at the head of each loop of a function.
+///
+/// # Arguments:
+/// * `data` - Contains the `BasicBlockData` and all argument, temp, and variable declarations from
+///            the MIR pass.
+/// * `names` - The variables the bounds may refer to, each with the name it is written with in
+///             conditions.
+///
+/// # Return Value:
+/// * Returns the bounds found at the head of each loop, by the index of the head, each as an
+///   Expression and as the text of a condition.
+///
+/// # Remarks:
+/// * The analysis computes, for each block, constraints of the forms `x <= c`, `x >= c` and
+///   `x - y <= c` (a zone), from the assignments and branch conditions leading to it. Loops are
+///   iterated to a fixed point, widening at their heads, and then narrowed.
+/// * Arithmetic that overflows panics, unless the function wraps around, so the result of a
+///   checked addition or subtraction is exact.
+/// * The bounds are candidates only: they are not guaranteed to be invariants, and must be proved
+///   before they are used.
+///
+pub fn infer_invariants(data: &mut MirData, names: &[(VariableMappingData, String)])
+                        -> HashMap<usize, Vec<(Expression, String)>> {
+    let variables = gen_variables(data, names);
+    let count = data.block_data.len();
+
+    // Every variable holds a value of its type on entry
+    let mut initial = Zone::new(variables.vars.len());
+    for x in 1..(variables.vars.len() + 1) {
+        clamp(&mut initial, x, &variables.vars[x - 1].var_type);
+    }
+    let initial = match feasible(initial) {
+        Some(zone) => zone,
+        None => return HashMap::new(),
+    };
+
+    // Iterate to a fixed point, widening at the heads of loops so that it is reached
+    let mut states: Vec<Option<Zone>> = vec![None; count];
+    states[0] = Some(initial.clone());
+    let mut pending: Vec<bool> = vec![false; count];
+    pending[0] = true;
+    loop {
+        let next = pending.iter().position(|&p| p);
+        let index = match next {
+            Some(i) => i,
+            None => break,
+        };
+        pending[index] = false;
+        let zone = match states[index].clone() {
+            Some(z) => z,
+            None => continue,
+        };
+        for (successor, reached) in transfer(index, zone, &variables, data) {
+            let updated = match states[successor] {
+                None => reached,
+                Some(ref old) => {
+                    let joined = old.join(&reached);
+                    if data.loops.contains_key(&successor) {
+                        old.widen(&joined)
+                    } else {
+                        joined
+                    }
+                },
+            };
+            if states[successor].as_ref() != Some(&updated) {
+                states[successor] = Some(updated);
+                pending[successor] = true;
+            }
+        }
+    }
+
+    // Recover some of the bounds lost by widening
+    for _ in 0..2 {
+        let mut narrowed: Vec<Option<Zone>> = vec![None; count];
+        narrowed[0] = Some(initial.clone());
+        for index in 0..count {
+            let zone = match states[index].clone() {
+                Some(z) => z,
+                None => continue,
+            };
+            for (successor, reached) in transfer(index, zone, &variables, data) {
+                let merged = match narrowed[successor].take() {
+                    Some(z) => z.join(&reached),
+                    None => reached,
+                };
+                narrowed[successor] = Some(merged);
+            }
+        }
+        states = narrowed;
+    }
+
+    let mut invariants: HashMap<usize, Vec<(Expression, String)>> = HashMap::new();
+    let heads: Vec<usize> = data.loops.keys().cloned().collect();
+    for head in heads {
+        let candidates = match states[head].clone().and_then(feasible) {
+            Some(zone) => gen_candidates(&zone, &variables, names),
+            // The head is never reached, so anything holds there
+            None => vec![(Expression::BooleanLiteral(false), "false".to_string())],
+        };
+        invariants.insert(head, candidates);
+    }
+    invariants
+}
+
+// A conjunction of constraints x - y <= c between the integer variables of a function. Variable 0
+// stands for the constant zero, so that x <= c is x - 0 <= c. A missing constraint is None.
+#[derive(Clone, PartialEq)]
+struct Zone {
+    bounds: Vec<Vec<Option<i64>>>,
+}
+
+impl Zone {
+    // Returns the zone of n variables without constraints
+    fn new(n: usize) -> Zone {
+        let mut bounds = vec![vec![None; n + 1]; n + 1];
+        for i in 0..(n + 1) {
+            bounds[i][i] = Some(0);
+        }
+        Zone { bounds: bounds }
+    }
+
+    // Tightens each constraint to the one implied by the others. Returns false if the constraints
+    // cannot all hold.
+    fn close(&mut self) -> bool {
+        let n = self.bounds.len();
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    let through = add(self.bounds[i][k], self.bounds[k][j]);
+                    let tightest = min(self.bounds[i][j], through);
+                    self.bounds[i][j] = tightest;
+                }
+            }
+        }
+        (0..n).all(|i| self.bounds[i][i].map_or(true, |b| b >= 0))
+    }
+
+    // Adds the constraint x - y <= c
+    fn constrain(&mut self, x: usize, y: usize, c: i64) {
+        let tightest = min(self.bounds[x][y], Some(c));
+        self.bounds[x][y] = tightest;
+    }
+
+    // Removes the constraints on x, keeping those they imply between the other variables
+    fn forget(&mut self, x: usize) {
+        self.close();
+        for i in 0..self.bounds.len() {
+            if i != x {
+                self.bounds[x][i] = None;
+                self.bounds[i][x] = None;
+            }
+        }
+    }
+
+    // Assigns y + c to x
+    fn assign(&mut self, x: usize, y: usize, c: i64) {
+        if x == y {
+            // Every constraint on x moves by c
+            for i in 0..self.bounds.len() {
+                if i != x {
+                    let above = add(self.bounds[x][i], Some(c));
+                    let below = c.checked_neg().and_then(|d| add(self.bounds[i][x], Some(d)));
+                    self.bounds[x][i] = above;
+                    self.bounds[i][x] = below;
+                }
+            }
+        } else {
+            self.forget(x);
+            self.constrain(x, y, c);
+            if let Some(d) = c.checked_neg() {
+                self.constrain(y, x, d);
+            }
+        }
+    }
+
+    // Returns the zone of the values in either zone
+    fn join(&self, other: &Zone) -> Zone {
+        Zone {
+            bounds: self.bounds.iter().zip(other.bounds.iter()).map(|(row, other_row)| {
+                row.iter().zip(other_row.iter()).map(|(&a, &b)| max(a, b)).collect()
+            }).collect(),
+        }
+    }
+
+    // Returns the zone of the values in either zone, dropping the constraints of this zone that
+    // the other one loosens, so that repeated widening stops
+    fn widen(&self, other: &Zone) -> Zone {
+        Zone {
+            bounds: self.bounds.iter().zip(other.bounds.iter()).map(|(row, other_row)| {
+                row.iter().zip(other_row.iter()).map(|(&a, &b)| {
+                    match (a, b) {
+                        (Some(a), Some(b)) if b <= a => Some(a),
+                        _ => None,
+                    }
+                }).collect()
+            }).collect(),
+        }
+    }
+
+    // Returns the largest value of x, if it has one
+    fn upper(&self, x: usize) -> Option<i64> {
+        self.bounds[x][0]
+    }
+
+    // Returns the smallest value of x, if it has one
+    fn lower(&self, x: usize) -> Option<i64> {
+        self.bounds[0][x].and_then(|b| b.checked_neg())
+    }
+}
+
+// Adds two constraints. The sum is dropped if it overflows.
+fn add(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+    match (a, b) {
+        (Some(a), Some(b)) => a.checked_add(b),
+        _ => None,
+    }
+}
+
+// Returns the tighter of two constraints
+fn min(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if a < b { a } else { b }),
+        (Some(a), None) | (None, Some(a)) => Some(a),
+        (None, None) => None,
+    }
+}
+
+// Returns the looser of two constraints
+fn max(a: Option<i64>, b: Option<i64>) -> Option<i64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if a > b { a } else { b }),
+        _ => None,
+    }
+}
+
+// Returns the closed zone, or None if its constraints cannot all hold
+fn feasible(mut zone: Zone) -> Option<Zone> {
+    if zone.close() {
+        Some(zone)
+    } else {
+        None
+    }
+}
+
+// The integer variables of a function, and their indices in a Zone
+struct Variables {
+    // The variable with index i is vars[i - 1]
+    vars: Vec<VariableMappingData>,
+    index: HashMap<String, usize>,
+}
+
+// Returns the integer variables that the statements of a function assign, and those in names
+fn gen_variables(data: &mut MirData, names: &[(VariableMappingData, String)]) -> Variables {
+    let mut vars: Vec<VariableMappingData> = names.iter().map(|&(ref v, _)| v.clone()).collect();
+    vars.extend(gen_assigned(data));
+
+    let mut variables = Variables { vars: Vec::new(), index: HashMap::new() };
+    for var in vars {
+        let integer = is_signed_type(var.var_type.clone()) || is_unsigned_type(var.var_type.clone());
+        if integer && !variables.index.contains_key(&var.name) {
+            variables.index.insert(var.name.clone(), variables.vars.len() + 1);
+            variables.vars.push(var);
+        }
+    }
+    variables
+}
+
+// Returns the variables that the statements and calls of a function assign
+fn gen_assigned(data: &mut MirData) -> Vec<VariableMappingData> {
+    let mut vars: Vec<VariableMappingData> = Vec::new();
+    for index in 0..data.block_data.len() {
+        for stmt in data.block_data[index].statements.clone() {
+            if let StatementKind::Assign(ref lval, ref rval) = stmt.kind {
+                if is_reference(lval, data) {
+                    continue;
+                }
+                let mut var = gen_lvalue(lval.clone(), data);
+                if let Rvalue::CheckedBinaryOp(..) = *rval {
+                    var.name = var.name + ".0";
+                }
+                vars.push(var);
+            }
+        }
+        let terminator = data.block_data[index].terminator().kind.clone();
+        if let TerminatorKind::Call{destination: Some((lval, _)), ..} = terminator {
+            vars.push(gen_lvalue(lval, data));
+        }
+    }
+    vars
+}
+
+// Returns the smallest and largest values of an integer type, if they fit in an i64
+fn type_range(ty: &Types) -> (Option<i64>, Option<i64>) {
+    let size = type_size(ty.clone()).unwrap_or(128);
+    if is_signed_type(ty.clone()) && size <= 64 {
+        let largest = ((1u64 << (size - 1)) - 1) as i64;
+        (Some(-largest - 1), Some(largest))
+    } else if is_unsigned_type(ty.clone()) && size < 64 {
+        (Some(0), Some(((1u64 << size) - 1) as i64))
+    } else if is_unsigned_type(ty.clone()) {
+        (Some(0), None)
+    } else {
+        (None, None)
+    }
+}
+
+// Constrains x to the values of its type
+fn clamp(zone: &mut Zone, x: usize, ty: &Types) {
+    let (smallest, largest) = type_range(ty);
+    if let Some(c) = smallest.and_then(|s| s.checked_neg()) {
+        zone.constrain(0, x, c);
+    }
+    if let Some(c) = largest {
+        zone.constrain(x, 0, c);
+    }
+}
+
+// A comparison l op r, where l and r are each a variable plus a constant
+#[derive(Clone)]
+struct Comparison {
+    op: BinOp,
+    left: (usize, i64),
+    right: (usize, i64),
+}
+
+// Returns the variable and constant that an operand is the sum of, if it is a variable that is
+// tracked or an integer constant
+fn term(operand: &Operand, variables: &Variables, data: &mut MirData) -> Option<(usize, i64)> {
+    match gen_expression(operand, data) {
+        Expression::VariableMapping(ref v) => variables.index.get(&v.name).map(|&x| (x, 0)),
+        Expression::SignedBitVector(ref s) => Some((0, s.value)),
+        Expression::UnsignedBitVector(ref u) if u.value <= i64::MAX as u64 => {
+            Some((0, u.value as i64))
+        },
+        _ => None,
+    }
+}
+
+// Checks whether a binary operation compares its operands
+fn is_comparison(op: &BinOp) -> bool {
+    match *op {
+        BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge | BinOp::Eq | BinOp::Ne => true,
+        _ => false,
+    }
+}
+
+// Returns the comparison that holds when the given one does not
+fn negation(op: &BinOp) -> BinOp {
+    match *op {
+        BinOp::Lt => BinOp::Ge,
+        BinOp::Le => BinOp::Gt,
+        BinOp::Gt => BinOp::Le,
+        BinOp::Ge => BinOp::Lt,
+        BinOp::Eq => BinOp::Ne,
+        BinOp::Ne => BinOp::Eq,
+        ref other => other.clone(),
+    }
+}
+
+// Adds the constraints that hold when a comparison holds
+fn assume(zone: &mut Zone, comparison: &Comparison) {
+    // x + a op y + b, that is x - y op b - a
+    let (x, a) = comparison.left;
+    let (y, b) = comparison.right;
+    let d = match b.checked_sub(a) {
+        Some(d) => d,
+        None => return,
+    };
+    match comparison.op {
+        BinOp::Lt => if let Some(c) = d.checked_sub(1) { zone.constrain(x, y, c) },
+        BinOp::Le => zone.constrain(x, y, d),
+        BinOp::Gt => if let Some(c) = d.checked_neg().and_then(|c| c.checked_sub(1)) {
+            zone.constrain(y, x, c)
+        },
+        BinOp::Ge => if let Some(c) = d.checked_neg() { zone.constrain(y, x, c) },
+        BinOp::Eq => {
+            zone.constrain(x, y, d);
+            if let Some(c) = d.checked_neg() {
+                zone.constrain(y, x, c);
+            }
+        },
+        _ => {},
+    }
+}
+
+// Returns the zones at the start of the blocks a block continues to, given the zone at its start.
+// Blocks that cannot be reached are left out.
+fn transfer(index: usize, mut zone: Zone, variables: &Variables, data: &mut MirData)
+            -> Vec<(usize, Zone)> {
+    // The comparisons held by boolean variables
+    let mut comparisons: HashMap<String, Comparison> = HashMap::new();
+    for stmt in data.block_data[index].statements.clone() {
+        let (lval, rval) = if let StatementKind::Assign(ref l, ref r) = stmt.kind {
+            (l.clone(), r.clone())
+        } else {
+            continue;
+        };
+        if is_reference(&lval, data) {
+            continue;
+        }
+        let mut var = gen_lvalue(lval, data);
+        if let Rvalue::CheckedBinaryOp(..) = rval {
+            var.name = var.name + ".0";
+        }
+
+        let comparison = match rval {
+            Rvalue::BinaryOp(ref op, ref l, ref r) if is_comparison(op) => {
+                match (term(l, variables, data), term(r, variables, data)) {
+                    (Some(l), Some(r)) => Some(Comparison { op: op.clone(), left: l, right: r }),
+                    _ => None,
+                }
+            },
+            Rvalue::UnaryOp(UnOp::Not, Operand::Consume(ref c)) => {
+                let negated = gen_lvalue(c.clone(), data);
+                comparisons.get(&negated.name).map(|c| Comparison {
+                    op: negation(&c.op),
+                    left: c.left,
+                    right: c.right,
+                })
+            },
+            _ => None,
+        };
+        match comparison {
+            Some(c) => { comparisons.insert(var.name.clone(), c); },
+            None => { comparisons.remove(&var.name); },
+        }
+
+        let x = match variables.index.get(&var.name) {
+            Some(&x) => x,
+            None => continue,
+        };
+        let checked = data.arith == Arithmetic::Checked;
+        let value = match rval {
+            Rvalue::Use(ref o) => term(o, variables, data),
+            // The sum is exact, as the function panics if it overflows
+            Rvalue::CheckedBinaryOp(BinOp::Add, ref l, ref r) if checked => {
+                match (term(l, variables, data), term(r, variables, data)) {
+                    (Some((y, a)), Some((0, c))) | (Some((0, c)), Some((y, a))) => {
+                        a.checked_add(c).map(|d| (y, d))
+                    },
+                    _ => None,
+                }
+            },
+            Rvalue::CheckedBinaryOp(BinOp::Sub, ref l, ref r) if checked => {
+                match (term(l, variables, data), term(r, variables, data)) {
+                    (Some((y, a)), Some((0, c))) => a.checked_sub(c).map(|d| (y, d)),
+                    _ => None,
+                }
+            },
+            _ => None,
+        };
+        match value {
+            Some((y, c)) => zone.assign(x, y, c),
+            None => zone.forget(x),
+        }
+        clamp(&mut zone, x, &variables.vars[x - 1].var_type);
+
+        // Comparisons of the old value of x no longer hold
+        let stale: Vec<String> = comparisons.iter()
+                                            .filter(|&(_, c)| c.left.0 == x || c.right.0 == x)
+                                            .map(|(name, _)| name.clone())
+                                            .collect();
+        for name in stale {
+            comparisons.remove(&name);
+        }
+    }
+
+    let mut reached: Vec<(usize, Zone)> = Vec::new();
+    let terminator = data.block_data[index].terminator().kind.clone();
+    match terminator {
+        TerminatorKind::If{cond, targets} => {
+            let comparison = match gen_expression(&cond, data) {
+                Expression::VariableMapping(ref v) => comparisons.get(&v.name).cloned(),
+                _ => None,
+            };
+            let mut taken = zone.clone();
+            let mut not_taken = zone;
+            if let Some(c) = comparison {
+                assume(&mut taken, &c);
+                assume(&mut not_taken, &Comparison {
+                    op: negation(&c.op),
+                    left: c.left,
+                    right: c.right,
+                });
+            }
+            reached.push((targets.0.index(), taken));
+            reached.push((targets.1.index(), not_taken));
+        },
+        // The result of a call may be any value of its type. Unwinding is not followed.
+        TerminatorKind::Call{destination: Some((lval, target)), ..} => {
+            let var = gen_lvalue(lval, data);
+            if let Some(&x) = variables.index.get(&var.name) {
+                zone.forget(x);
+                clamp(&mut zone, x, &var.var_type);
+            }
+            reached.push((target.index(), zone));
+        },
+        _ => {
+            for successor in successors(index, data) {
+                reached.push((successor, zone.clone()));
+            }
+        },
+    }
+    reached.into_iter().filter_map(|(successor, z)| feasible(z).map(|z| (successor, z))).collect()
+}
+
+// Returns the conditions a zone gives on the variables in names, leaving out those that follow
+// from their types or from the other conditions
+fn gen_candidates(zone: &Zone, variables: &Variables, names: &[(VariableMappingData, String)])
+                  -> Vec<(Expression, String)> {
+    let tracked: Vec<(usize, &VariableMappingData, &String)> = names.iter().filter_map(|n| {
+        variables.index.get(&n.0.name).map(|&x| (x, &n.0, &n.1))
+    }).collect();
+    let mut candidates: Vec<(Expression, String)> = Vec::new();
+
+    // Bounds on single variables
+    for &(x, var, source) in tracked.iter() {
+        let (smallest, largest) = type_range(&var.var_type);
+        let (lower, upper) = (zone.lower(x), zone.upper(x));
+        if lower.is_some() && lower == upper {
+            candidates.extend(bound(var, source, BinaryOperator::Equal, lower.unwrap()));
+            continue;
+        }
+        if let Some(l) = lower {
+            if smallest.map_or(true, |s| s < l) {
+                candidates.extend(bound(var, source, BinaryOperator::GreaterThanOrEqual, l));
+            }
+        }
+        if let Some(u) = upper {
+            if largest.map_or(true, |s| u < s) {
+                candidates.extend(bound(var, source, BinaryOperator::LessThanOrEqual, u));
+            }
+        }
+    }
+
+    // x < y, x <= y and x == y between variables of the same type
+    for (i, &(x, x_var, x_source)) in tracked.iter().enumerate() {
+        for &(y, y_var, y_source) in tracked.iter().skip(i + 1) {
+            if x_var.var_type != y_var.var_type {
+                continue;
+            }
+            let (above, below) = (zone.bounds[x][y], zone.bounds[y][x]);
+            if above == Some(0) && below == Some(0) {
+                candidates.push(relation(x_var, x_source, BinaryOperator::Equal, y_var, y_source));
+                continue;
+            }
+            // x - y <= c follows from the bounds on x and y if upper(x) - lower(y) <= c
+            let implied = |a: usize, b: usize, c: i64| {
+                add(zone.upper(a), zone.lower(b).and_then(|l| l.checked_neg()))
+                    .map_or(false, |d| d <= c)
+            };
+            for &(a, a_var, a_source, b, b_var, b_source, c) in
+                [(x, x_var, x_source, y, y_var, y_source, above),
+                 (y, y_var, y_source, x, x_var, x_source, below)].iter() {
+                let op = match c {
+                    Some(0) => BinaryOperator::LessThanOrEqual,
+                    Some(-1) => BinaryOperator::LessThan,
+                    _ => continue,
+                };
+                if !implied(a, b, c.unwrap()) {
+                    candidates.push(relation(a_var, a_source, op, b_var, b_source));
+                }
+            }
+        }
+    }
+    candidates
+}
+
+// Returns the condition x op c, if c is a value of the type of x
+fn bound(var: &VariableMappingData, source: &str, op: BinaryOperator, c: i64)
+         -> Option<(Expression, String)> {
+    typed_integer_literal(c.to_string().as_str(), &var.var_type).ok().map(|literal| {
+        let text = format!("{} {} {}", source, op, c);
+        (Expression::BinaryExpression( BinaryExpressionData {
+            op: op,
+            left: Box::new(Expression::VariableMapping(var.clone())),
+            right: Box::new(literal),
+        } ), text)
+    })
+}
+
+// Returns the condition x op y
+fn relation(x: &VariableMappingData,
+            x_source: &str,
+            op: BinaryOperator,
+            y: &VariableMappingData,
+            y_source: &str)
+            -> (Expression, String) {
+    let text = format!("{} {} {}", x_source, op, y_source);
+    (Expression::BinaryExpression( BinaryExpressionData {
+        op: op,
+        left: Box::new(Expression::VariableMapping(x.clone())),
+        right: Box::new(Expression::VariableMapping(y.clone())),
+    } ), text)
+}
+
+/// Builds candidate loop invariants from templates over the variables and constants of a function.
+///
+/// # Arguments:
+/// * `data` - Contains the `BasicBlockData` and all argument, temp, and variable declarations from
+///            the MIR pass.
+/// * `names` - The variables the candidates may refer to, each with the name it is written with in
+///             conditions.
+/// * `params` - The arguments of the function.
+///
+/// # Return Value:
+/// * Returns the candidates, each as an Expression and as the text of a condition.
+///
+/// # Remarks:
+/// * The templates are `x >= 0` for signed `x`; `x <= c`, `x >= c` and `x == c` for the integer
+///   constants `c` in the function's code; `x < y`, `x <= y` and `x == y`; and, for variables `x`
+///   the function assigns and arguments `a`, `x == old(a) + y` and `x == old(a) - y`. The variables
+///   of each candidate have the same type.
+/// * Most candidates are not invariants. They are pruned by proving them (see `houdini`).
+///
+pub fn gen_templates(data: &mut MirData,
+                     names: &[(VariableMappingData, String)],
+                     params: &[VariableMappingData])
+                     -> Vec<(Expression, String)> {
+    let names: Vec<&(VariableMappingData, String)> = names.iter().filter(|&&(ref v, _)| {
+        is_signed_type(v.var_type.clone()) || is_unsigned_type(v.var_type.clone())
+    }).collect();
+    let assigned: Vec<String> = gen_assigned(data).into_iter().map(|v| v.name).collect();
+    let constants = gen_constants(data);
+    let mut candidates: Vec<(Expression, String)> = Vec::new();
+
+    for &&(ref x, ref x_source) in names.iter() {
+        let (smallest, largest) = type_range(&x.var_type);
+        if is_signed_type(x.var_type.clone()) {
+            candidates.extend(bound(x, x_source, BinaryOperator::GreaterThanOrEqual, 0));
+        }
+        for &c in constants.iter() {
+            if smallest.map_or(true, |s| s < c) {
+                candidates.extend(bound(x, x_source, BinaryOperator::GreaterThanOrEqual, c));
+            }
+            if largest.map_or(true, |l| c < l) {
+                candidates.extend(bound(x, x_source, BinaryOperator::LessThanOrEqual, c));
+            }
+            candidates.extend(bound(x, x_source, BinaryOperator::Equal, c));
+        }
+    }
+
+    for (i, &&(ref x, ref x_source)) in names.iter().enumerate() {
+        for (j, &&(ref y, ref y_source)) in names.iter().enumerate() {
+            if i == j || x.var_type != y.var_type {
+                continue;
+            }
+            candidates.push(relation(x, x_source, BinaryOperator::LessThan, y, y_source));
+            candidates.push(relation(x, x_source, BinaryOperator::LessThanOrEqual, y, y_source));
+            if i < j {
+                candidates.push(relation(x, x_source, BinaryOperator::Equal, y, y_source));
+            }
+            if !assigned.contains(&x.name) {
+                continue;
+            }
+            for a in params.iter().filter(|a| a.var_type == x.var_type && a.name != y.name) {
+                for op in [BinaryOperator::Addition, BinaryOperator::Subtraction].iter() {
+                    let text = format!("{} == old({}) {} {}", x_source, a.name, op, y_source);
+                    let change = Expression::BinaryExpression( BinaryExpressionData {
+                        op: op.clone(),
+                        left: Box::new(Expression::UnaryExpression( UnaryExpressionData {
+                            op: UnaryOperator::Old,
+                            e: Box::new(Expression::VariableMapping(a.clone())),
+                        } )),
+                        right: Box::new(Expression::VariableMapping(y.clone())),
+                    } );
+                    candidates.push((Expression::BinaryExpression( BinaryExpressionData {
+                        op: BinaryOperator::Equal,
+                        left: Box::new(Expression::VariableMapping(x.clone())),
+                        right: Box::new(change),
+                    } ), text));
+                }
+            }
+        }
+    }
+    candidates
+}
+
+// Returns the integer constants in the code of a function that fit in an i64, without repeats,
+// up to a small number of them
+fn gen_constants(data: &mut MirData) -> Vec<i64> {
+    let mut constants: Vec<i64> = Vec::new();
+    for index in 0..data.block_data.len() {
+        for stmt in data.block_data[index].statements.clone() {
+            if let StatementKind::Assign(_, ref rval) = stmt.kind {
+                let operands = match *rval {
+                    Rvalue::Use(ref o) => vec![o.clone()],
+                    Rvalue::BinaryOp(_, ref l, ref r) | Rvalue::CheckedBinaryOp(_, ref l, ref r) => {
+                        vec![l.clone(), r.clone()]
+                    },
+                    _ => Vec::new(),
+                };
+                for operand in operands.iter().filter(|o| match **o {
+                    Operand::Constant(..) => true,
+                    _ => false,
+                }) {
+                    let value = match gen_expression(operand, data) {
+                        Expression::SignedBitVector(ref s) => Some(s.value),
+                        Expression::UnsignedBitVector(ref u) if u.value <= i64::MAX as u64 => {
+                            Some(u.value as i64)
+                        },
+                        _ => None,
+                    };
+                    if let Some(c) = value {
+                        if !constants.contains(&c) && constants.len() < 4 {
+                            constants.push(c);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    constants
+}
@@ -12,15 +12,21 @@
 
 extern crate rustc_const_math;
 
-use super::MirData;
+use std::mem;
+
+use super::{MirData, Contract};
 use expression::*;
+use parser::Arithmetic;
 use rustc::mir::repr::*;
 use rustc::middle::const_val::ConstVal;
-use rustc_const_math::ConstInt;
+use rustc_const_math::{ConstInt, ConstIsize, ConstUsize};
 use rustc_data_structures::indexed_vec::Idx;
 use rustc::ty::{TypeVariants};
 
 mod overflow;
+mod inference;
+
+pub use self::inference::{infer_invariants, gen_templates};
 
 /// Computes the weakest precondition for a given postcondition and a series of statements over one or more MIR basic blocks.
 ///
@@ -34,8 +40,27 @@ mod overflow;
 ///
 /// # Remarks:
 /// * This is the main generator for the weakest precondition, which evaluates the `BasicBlock`s recursively.
+/// * The head of a loop is a cut point: the weakest precondition of reaching it is given by the
+///   loop's invariant (see `gen_loop`).
+/// * If the function is verified by unrolling, its loops are unrolled instead (see
+///   `gen_unrolled`).
 ///
 pub fn gen(index: usize, data: &mut MirData, post_expr: &Option<Expression>, debug: bool) -> Option<Expression> {
+    if let Some(bound) = data.unroll {
+        return gen_unrolled(index, bound, data, post_expr, debug);
+    }
+    if data.loops.contains_key(&index) {
+        if data.loop_stack.contains(&index) {
+            return Some(gen_back_edge(index, data, post_expr, debug));
+        }
+        return Some(gen_loop(index, data, post_expr, debug));
+    }
+    gen_block(index, data, post_expr, debug)
+}
+
+// Computes the weakest precondition of a block, from those of the blocks it leads to
+fn gen_block(index: usize, data: &mut MirData, post_expr: &Option<Expression>, debug: bool)
+             -> Option<Expression> {
     let mut wp: Option<Expression>;
 
     // Parse basic block terminator data
@@ -52,22 +77,46 @@ pub fn gen(index: usize, data: &mut MirData, post_expr: &Option<Expression>, deb
             return post_expr.clone();
         },
         // Call{func, args, destination, cleanup}
-        TerminatorKind::Call{func, ..} => {
-            // Determine if this is the end of a panic. (assumed false branch of assertion, so
-            // return a precondition of false [this path will never be taken])
-            match func {
+        TerminatorKind::Call{func, args, destination, ..} => {
+            let name = match func {
+                Operand::Constant (ref c) => format!("{:?}", c.literal),
+                // Consume (ref l)
+                Operand::Consume (..) => {
+                    rp_unsupported!("Unsupported call through a function pointer")
+                },
+            };
+            // A function of the crate is never modelled as one of the standard library
+            let external = match func {
                 Operand::Constant (ref c) => {
-                    let s = format!("{:?}", c.literal);
-                    if s.contains("begin_panic") {
-                        return Some(Expression::BooleanLiteral(false));
+                    match c.literal {
+                        Literal::Item {def_id, ..} => !def_id.is_local(),
+                        _ => false,
                     }
                 },
-                // Consume (ref l)
-                Operand::Consume (..) => unimplemented!(),
+                Operand::Consume (..) => false,
             };
-            // Due to the limited nature in which we handle Calls, we should never do anything
-            // other than return early or hit the unimplemented!() panic above.
-            unreachable!();
+            // Determine if this is the end of a panic. (assumed false branch of assertion, so
+            // return a precondition of false [this path will never be taken])
+            if name.contains("begin_panic") {
+                return Some(data.panic_expr.clone());
+            }
+            // Otherwise, only recursive calls and calls to the functions gen_call supports are
+            // handled
+            match destination {
+                Some((lvalue, target)) => {
+                    let wp_after = gen(target.index(), data, post_expr, debug).unwrap();
+                    let contract = data.contract.clone();
+                    wp = match contract {
+                        Some(ref c) if c.name == name => {
+                            Some(gen_recursive_call(wp_after, c, &args, lvalue, data))
+                        },
+                        _ => {
+                            Some(gen_call(wp_after, name.as_str(), external, &args, lvalue, data))
+                        },
+                    };
+                },
+                None => rp_unsupported!("Unsupported call to a diverging function"),
+            }
         },
         // Conditional statements
         // wp(if c x else y) => (c -> x) AND ((NOT c) -> y)
@@ -88,7 +137,7 @@ pub fn gen(index: usize, data: &mut MirData, post_expr: &Option<Expression>, deb
                                 _ => unreachable!(),
                             }
                         },
-                        _ => unimplemented!(),
+                        _ => rp_unsupported!("Unsupported constant condition"),
                     }
                 },
                 Operand::Consume(c) => { Expression::VariableMapping(gen_lvalue(c, data)) },
@@ -115,15 +164,40 @@ pub fn gen(index: usize, data: &mut MirData, post_expr: &Option<Expression>, deb
         },
         // Unimplemented TerminatorKinds
         // DropAndReplace{location, value, target, unwind}
-        TerminatorKind::DropAndReplace{..} => unimplemented!(),
+        TerminatorKind::DropAndReplace{..} => rp_unsupported!("Unsupported drop"),
         // Drop{location, target, unwind}
-        TerminatorKind::Drop{..} => unimplemented!(),
-        TerminatorKind::Unreachable => unimplemented!(),
-        TerminatorKind::Resume => unimplemented!(),
+        TerminatorKind::Drop{..} => rp_unsupported!("Unsupported drop"),
+        TerminatorKind::Unreachable => rp_unsupported!("Unsupported unreachable code"),
+        TerminatorKind::Resume => rp_unsupported!("Unsupported unwinding"),
         // Switch{discr, adt_def, targets}
-        TerminatorKind::Switch{..} => unimplemented!(),
+        // wp(match o { None => x, Some(..) => y }) => (o.is_some -> y) AND ((NOT o.is_some) -> x)
+        TerminatorKind::Switch{discr, targets, ..} => {
+            if !is_option(&discr, data) {
+                rp_unsupported!("Unsupported match: only matches on Option are supported");
+            }
+            // The variants of Option are None, then Some
+            let wp_none = gen(targets[0].index(), data, post_expr, debug);
+            let wp_some = gen(targets[1].index(), data, post_expr, debug);
+            let is_some = Expression::VariableMapping(option_flag(&gen_lvalue(discr, data)));
+            wp = Some(Expression::BinaryExpression(BinaryExpressionData {
+                op: BinaryOperator::And,
+                left: Box::new(Expression::BinaryExpression(BinaryExpressionData {
+                    op: BinaryOperator::Implication,
+                    left: Box::new(is_some.clone()),
+                    right: Box::new(wp_some.unwrap())
+                })),
+                right: Box::new(Expression::BinaryExpression(BinaryExpressionData {
+                    op: BinaryOperator::Implication,
+                    left: Box::new(Expression::UnaryExpression(UnaryExpressionData {
+                        op: UnaryOperator::Not,
+                        e: Box::new(is_some)
+                    })),
+                    right: Box::new(wp_none.unwrap())
+                }))
+            }));
+        },
         // SwitchInt{discr, switch_ty, values, targets}
-        TerminatorKind::SwitchInt{..} => unimplemented!(),
+        TerminatorKind::SwitchInt{..} => rp_unsupported!("Unsupported match on an integer"),
     }
 
     // Examine the statements in reverse order
@@ -149,6 +223,379 @@ pub fn gen(index: usize, data: &mut MirData, post_expr: &Option<Expression>, deb
     wp
 }
 
+/// What the weakest precondition of a function requires of its loops
+#[derive(Clone, Copy, PartialEq)]
+pub enum Goal {
+    // The invariants are assumed, and the postcondition must hold
+    Postcondition,
+    // The invariant of the loop with the given head must hold on entry, and after each iteration
+    Invariant(usize),
+    // The variant of the loop with the given head must decrease on each iteration
+    Termination(usize),
+    // The loop with the given head must exit within the unrolled iterations
+    Unwinding(usize),
+    // The candidate invariant with the given index, of the loop with the given head, must hold on
+    // entry and after each iteration
+    Candidate(usize, usize),
+    // The invariant of the loop with the given head must hold the first k times the head is reached
+    BaseCase(usize, usize),
+    // The invariant of the loop with the given head must hold after any k consecutive iterations
+    // that each start in a state satisfying it
+    InductiveStep(usize, usize),
+}
+
+/// A loop in the control flow graph of a function
+#[derive(Clone)]
+pub struct Loop {
+    // The block each iteration starts at
+    pub head: usize,
+    // The blocks of the loop, its head included
+    pub blocks: Vec<usize>,
+    // Where the loop is written, for reporting
+    pub label: String,
+    // The condition that holds at the head of each iteration
+    pub invariant: Option<Expression>,
+    // The integer measure that each iteration must decrease
+    pub variant: Option<Expression>,
+    // The text of the invariant, if it was inferred rather than given
+    pub inferred: Option<String>,
+    // The conditions the inferred invariant is chosen from, each with its text
+    pub candidates: Vec<(Expression, String)>,
+}
+
+/// Finds the loops of a function.
+///
+/// # Arguments:
+/// * `data` - Contains the `BasicBlockData` and all argument, temp, and variable declarations from
+///            the MIR pass.
+///
+/// # Return Value:
+/// * Returns the loops, ordered by their heads, without labels, invariants or variants
+///
+/// # Remarks:
+/// * A loop is found for each block that is the target of a back edge of a depth-first search
+///   from the entry block. Its blocks are those that reach a back edge to the head without passing
+///   through the head.
+///
+pub fn find_loops(data: &MirData) -> Vec<Loop> {
+    let count = data.block_data.len();
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); count];
+    for index in 0..count {
+        for successor in successors(index, data) {
+            predecessors[successor].push(index);
+        }
+    }
+
+    // Depth-first search, recording the edges to blocks still on the stack
+    let mut back_edges: Vec<(usize, usize)> = Vec::new();
+    let mut visited: Vec<bool> = vec![false; count];
+    let mut on_stack: Vec<bool> = vec![false; count];
+    let mut stack: Vec<(usize, Vec<usize>)> = vec![(0, successors(0, data))];
+    visited[0] = true;
+    on_stack[0] = true;
+    while !stack.is_empty() {
+        let next = stack.last_mut().unwrap().1.pop();
+        match next {
+            Some(successor) => {
+                if on_stack[successor] {
+                    back_edges.push((stack.last().unwrap().0, successor));
+                } else if !visited[successor] {
+                    visited[successor] = true;
+                    on_stack[successor] = true;
+                    stack.push((successor, successors(successor, data)));
+                }
+            },
+            None => {
+                on_stack[stack.pop().unwrap().0] = false;
+            },
+        }
+    }
+
+    let mut heads: Vec<usize> = back_edges.iter().map(|&(_, head)| head).collect();
+    heads.sort();
+    heads.dedup();
+    heads.iter().map(|&head| {
+        let mut blocks: Vec<usize> = vec![head];
+        let mut work: Vec<usize> = back_edges.iter()
+                                             .filter(|&&(_, h)| h == head)
+                                             .map(|&(tail, _)| tail)
+                                             .collect();
+        while let Some(block) = work.pop() {
+            if !blocks.contains(&block) {
+                blocks.push(block);
+                work.extend(predecessors[block].iter().cloned());
+            }
+        }
+        blocks.sort();
+        Loop {
+            head: head,
+            blocks: blocks,
+            label: String::new(),
+            invariant: None,
+            variant: None,
+            inferred: None,
+            candidates: Vec::new(),
+        }
+    }).collect()
+}
+
+// Returns the blocks a block may continue to
+fn successors(index: usize, data: &MirData) -> Vec<usize> {
+    data.block_data[index].terminator().successors().iter().map(|b| b.index()).collect()
+}
+
+/// Returns the weakest precondition of reaching the head of a loop from outside it
+///
+/// # Arguments:
+/// * `head` - The index of the head of the loop
+/// * `data` - Contains the `BasicBlockData` and all argument, temp, and variable declarations from
+///            the MIR pass.
+/// * `post_expr` - The postcondition of the function.
+///
+/// # Return Value:
+/// * Returns `forall v. I => wp(body)`, where `v` are the variables the loop modifies, renamed,
+///   and `I` is the invariant; the invariant itself, or a candidate for it, is conjoined when
+///   checking it.
+///
+/// # Remarks:
+/// * Each iteration may start in any state satisfying the invariant. A path that returns to the
+///   head ends there, with the weakest precondition given by `gen_back_edge`.
+/// * When checking the variant, its value at the head is recorded by a snapshot variable, so that
+///   its value at the end of an iteration can be compared with it.
+/// * When checking the base case of k-induction, the iterations start in the entry state instead,
+///   which must satisfy the invariant.
+///
+fn gen_loop(head: usize, data: &mut MirData, post_expr: &Option<Expression>, debug: bool)
+            -> Expression {
+    let l = data.loops[&head].clone();
+    let invariant = match l.invariant {
+        Some(ref i) => i.clone(),
+        None => rp_error!("The loop at {} needs an invariant, given by a loop_condition attribute",
+                          l.label),
+    };
+    // Whether a function panics cannot be decided for a loop with an over-approximating invariant
+    if data.goal == Goal::Postcondition && data.panic_expr != Expression::BooleanLiteral(false) {
+        rp_error!("Unsupported loop at {}: a function with a panic condition may not contain loops",
+                  l.label);
+    }
+
+    // When the invariant is checked by k-induction, the iterations are counted
+    let (base, stepping) = match data.goal {
+        Goal::BaseCase(h, _) if h == head => (true, false),
+        Goal::InductiveStep(h, _) if h == head => (false, true),
+        _ => (false, false),
+    };
+    // The base case follows the iterations from the entry state, without a cut
+    if base {
+        data.loop_stack.push(head);
+        data.unwinding.push((head, 0));
+        let body = gen_block(head, data, post_expr, debug).unwrap();
+        data.unwinding.pop();
+        data.loop_stack.pop();
+        return conjunction(vec![invariant, body]);
+    }
+
+    data.loop_stack.push(head);
+    if stepping {
+        data.unwinding.push((head, 0));
+    }
+    let mut body = gen_block(head, data, post_expr, debug).unwrap();
+    if stepping {
+        data.unwinding.pop();
+    }
+    data.loop_stack.pop();
+    if let (Goal::Termination(h), Some(ref variant)) = (data.goal, l.variant.clone()) {
+        if h == head {
+            let snapshot = variant_snapshot(head, variant);
+            substitute_variable_with_expression(&mut body, &snapshot, variant);
+        }
+    }
+
+    // The variables the loop modifies may hold any values satisfying the invariant
+    let mut modified: Vec<String> = Vec::new();
+    for &block in l.blocks.iter() {
+        for stmt in data.block_data[block].statements.clone() {
+            if let StatementKind::Assign(ref lval, _) = stmt.kind {
+                modified.push(gen_lvalue(lval.clone(), data).name);
+            }
+        }
+        let terminator = data.block_data[block].terminator().kind.clone();
+        if let TerminatorKind::Call{destination: Some((lval, _)), ..} = terminator {
+            modified.push(gen_lvalue(lval, data).name);
+        }
+    }
+    let mut cut = Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::Implication,
+        left: Box::new(invariant.clone()),
+        right: Box::new(body),
+    } );
+    // Fields and flags of a modified variable are named after it
+    let havoc: Vec<VariableMappingData> = free_variables(&cut).into_iter().filter(|v| {
+        modified.iter().any(|m| v.name == *m || v.name.starts_with(&(m.clone() + ".")))
+    }).collect();
+    for var in havoc {
+        let renamed = VariableMappingData {
+            name: format!("{}!{}", var.name, head),
+            var_type: var.var_type.clone(),
+        };
+        substitute_state_variable(&mut cut,
+                                  &var,
+                                  &Expression::VariableMapping(renamed.clone()));
+        cut = Expression::Quantifier( QuantifierData {
+            quantifier: Quantifier::Forall,
+            var: renamed,
+            lower: None,
+            upper: None,
+            body: Box::new(cut),
+        } );
+    }
+
+    match data.goal {
+        Goal::Invariant(h) if h == head => conjunction(vec![invariant, cut]),
+        Goal::Candidate(h, k) if h == head => conjunction(vec![l.candidates[k].0.clone(), cut]),
+        _ => cut,
+    }
+}
+
+/// Returns the weakest precondition of returning to the head of a loop at the end of an iteration
+///
+/// # Arguments:
+/// * `head` - The index of the head of the loop
+/// * `data` - Contains the `BasicBlockData` and all argument, temp, and variable declarations from
+///            the MIR pass.
+///
+/// * `post_expr` - The postcondition of the function.
+///
+/// # Return Value:
+/// * Returns the invariant or candidate being checked, `0 <= V AND V < snapshot` when the variant
+///   `V` is being checked, and `true` otherwise
+///
+/// # Remarks:
+/// * When the invariant is checked by k-induction, the first `k - 1` returns to the head go on
+///   with another iteration: the base case requires the invariant there, and the inductive step
+///   assumes it. After `k` iterations, the base case ends with `true` and the inductive step
+///   with the invariant.
+///
+fn gen_back_edge(head: usize, data: &mut MirData, post_expr: &Option<Expression>, debug: bool)
+                 -> Expression {
+    match data.goal {
+        Goal::BaseCase(h, k) | Goal::InductiveStep(h, k) if h == head => {
+            let base = data.goal == Goal::BaseCase(h, k);
+            let invariant = data.loops[&head].invariant.clone().unwrap();
+            let entry = data.unwinding.iter().rposition(|&(l, _)| l == head).unwrap();
+            let iterations = data.unwinding[entry].1 + 1;
+            if iterations == k {
+                return if base { Expression::BooleanLiteral(true) } else { invariant };
+            }
+            data.unwinding[entry].1 = iterations;
+            let rest = gen_block(head, data, post_expr, debug).unwrap();
+            data.unwinding[entry].1 = iterations - 1;
+            if base {
+                return conjunction(vec![invariant, rest]);
+            }
+            return Expression::BinaryExpression( BinaryExpressionData {
+                op: BinaryOperator::Implication,
+                left: Box::new(invariant),
+                right: Box::new(rest),
+            } );
+        },
+        _ => {},
+    }
+
+    let l = &data.loops[&head];
+    match (data.goal, l.variant.as_ref()) {
+        (Goal::Invariant(h), _) if h == head => l.invariant.clone().unwrap(),
+        (Goal::Candidate(h, k), _) if h == head => l.candidates[k].0.clone(),
+        (Goal::Termination(h), Some(variant)) if h == head => {
+            let zero = match typed_integer_literal("0", &determine_evaluation_type(variant)) {
+                Ok(z) => z,
+                Err(e) => rp_error!("{}", e),
+            };
+            Expression::BinaryExpression( BinaryExpressionData {
+                op: BinaryOperator::And,
+                left: Box::new(Expression::BinaryExpression( BinaryExpressionData {
+                    op: BinaryOperator::LessThanOrEqual,
+                    left: Box::new(zero),
+                    right: Box::new(variant.clone()),
+                } )),
+                right: Box::new(Expression::BinaryExpression( BinaryExpressionData {
+                    op: BinaryOperator::LessThan,
+                    left: Box::new(variant.clone()),
+                    right: Box::new(Expression::VariableMapping(variant_snapshot(head, variant))),
+                } )),
+            } )
+        },
+        _ => Expression::BooleanLiteral(true),
+    }
+}
+
+// Returns the variable recording the value of a loop's variant at the start of an iteration
+fn variant_snapshot(head: usize, variant: &Expression) -> VariableMappingData {
+    VariableMappingData {
+        name: format!("variant!{}", head),
+        var_type: determine_evaluation_type(variant),
+    }
+}
+
+/// Computes the weakest precondition of a block, unrolling each loop to a bounded number of
+/// iterations
+///
+/// # Arguments:
+/// * `index` - The index of the block
+/// * `bound` - The number of iterations each loop is unrolled to
+/// * `data` - Contains the `BasicBlockData` and all argument, temp, and variable declarations from
+///            the MIR pass.
+/// * `post_expr` - The postcondition of the function.
+///
+/// # Return Value:
+/// * Returns the weakest precondition of the executions that start at the block and go round each
+///   loop at most `bound` times
+///
+/// # Remarks:
+/// * A path that returns to the head of a loop once more is cut off: its weakest precondition is
+///   `false` when checking that the loop exits in time, and `true` otherwise.
+///
+fn gen_unrolled(index: usize,
+                bound: usize,
+                data: &mut MirData,
+                post_expr: &Option<Expression>,
+                debug: bool)
+                -> Option<Expression> {
+    // Leaving a loop ends its iterations, so entering it again starts counting from zero
+    let unwinding = data.unwinding.clone();
+    {
+        let loops = &data.loops;
+        data.unwinding.retain(|&(head, _)| loops[&head].blocks.contains(&index));
+    }
+
+    let wp = if !data.loops.contains_key(&index) {
+        gen_block(index, data, post_expr, debug)
+    } else {
+        match data.unwinding.iter().position(|&(head, _)| head == index) {
+            None => {
+                data.unwinding.push((index, 0));
+                gen_block(index, data, post_expr, debug)
+            },
+            Some(entry) if data.unwinding[entry].1 < bound => {
+                data.unwinding[entry].1 += 1;
+                gen_block(index, data, post_expr, debug)
+            },
+            Some(_) => {
+                // Whether a function panics cannot be decided from some of its executions
+                if data.goal == Goal::Postcondition
+                   && data.panic_expr != Expression::BooleanLiteral(false) {
+                    rp_error!("Unsupported loop at {}: a function with a panic condition may not \
+                              contain loops", data.loops[&index].label);
+                }
+                Some(Expression::BooleanLiteral(data.goal != Goal::Unwinding(index)))
+            },
+        }
+    };
+
+    data.unwinding = unwinding;
+    wp
+}
+
 /// Returns a (possibly) modified weakest precondition based on the content of a statement
 ///
 /// # Arguments:
@@ -180,6 +627,15 @@ fn gen_stmt(mut wp: Expression, stmt: Statement, data: &mut MirData, debug: bool
         },
         //_ => return Some(wp)
     }
+    // References are replaced by their referents (see gen_references), so assigning one does not
+    // change the weakest precondition
+    if is_reference(lvalue.as_ref().unwrap(), data) {
+        return Some(wp);
+    }
+
+    // An Option also has a flag recording whether it is Some
+    let option = is_option(lvalue.as_ref().unwrap(), data);
+
     // The variable or temp on the left-hand side of the assignment
     let mut var = gen_lvalue(lvalue.unwrap(), data);
 
@@ -188,44 +644,103 @@ fn gen_stmt(mut wp: Expression, stmt: Statement, data: &mut MirData, debug: bool
     match rvalue.clone().unwrap() {
         Rvalue::CheckedBinaryOp(ref binop, ref loperand, ref roperand) => {
             let lvalue: Expression = gen_expression(loperand, data);
-            let rvalue: Expression = gen_expression(roperand, data);
+            let mut rvalue: Expression = gen_expression(roperand, data);
             let op: BinaryOperator = match *binop {
                 BinOp::Add => {
-                    // Add the overflow expression checks
-                    wp = overflow::overflow_check(&wp, &var, binop, &lvalue, &rvalue);
+                    if data.arith == Arithmetic::Checked {
+                        // Add the overflow expression checks
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
+                    }
                     BinaryOperator::Addition
                 },
                 BinOp::Sub => {
-                    // Add the overflow and underflow expression checks
-                    wp = overflow::overflow_check(&wp, &var, binop, &lvalue, &rvalue);
+                    if data.arith == Arithmetic::Checked {
+                        // Add the overflow and underflow expression checks
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
+                    }
                     BinaryOperator::Subtraction
                 },
                 BinOp::Mul => {
-                    // Add the overflow and underflow expression checks
-                    wp = overflow::overflow_check(&wp, &var, binop, &lvalue, &rvalue);
+                    if data.arith == Arithmetic::Checked {
+                        // Add the overflow and underflow expression checks
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
+                    }
                     BinaryOperator::Multiplication
                 },
                 BinOp::Div => {
                     // Add the overflow and underflow expression checks, if operands are signed
                     if is_signed_type(determine_evaluation_type(&rvalue)) {
-                        wp = overflow::overflow_check(&wp, &var, binop, &lvalue, &rvalue);
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
                     }
                     // Add the division by 0 expression check
-                    wp = add_zero_check(&wp, &rvalue);
+                    wp = add_zero_check(&wp, &rvalue, &data.panic_expr);
                     BinaryOperator::Division
                 },
                 BinOp::Rem => {
                     // Add the overflow and underflow expression checks, if operands are signed
                     if is_signed_type(determine_evaluation_type(&rvalue)) {
-                        wp = overflow::overflow_check(&wp, &var, binop, &lvalue, &rvalue);
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
                     }
                     // Add the division by 0 expression check
-                    wp = add_zero_check(&wp, &rvalue);
+                    wp = add_zero_check(&wp, &rvalue, &data.panic_expr);
                     BinaryOperator::Modulo
                 },
-                BinOp::Shl => BinaryOperator::BitwiseLeftShift,
-                BinOp::Shr => BinaryOperator::BitwiseRightShift,
-                _ => rp_error!("Unsupported checked binary operation!"),
+                BinOp::Shl => {
+                    if data.arith == Arithmetic::Checked {
+                        // Add the shift amount check
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
+                    } else {
+                        // The shift amount is masked to the width of the value shifted
+                        rvalue = masked_shift_amount(&rvalue, &var);
+                    }
+                    BinaryOperator::BitwiseLeftShift
+                },
+                BinOp::Shr => {
+                    if data.arith == Arithmetic::Checked {
+                        // Add the shift amount check
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
+                    } else {
+                        // The shift amount is masked to the width of the value shifted
+                        rvalue = masked_shift_amount(&rvalue, &var);
+                    }
+                    BinaryOperator::BitwiseRightShift
+                },
+                _ => rp_unsupported!("Unsupported checked binary operation!"),
             };
 
             var.name = var.name + ".0";
@@ -240,46 +755,115 @@ fn gen_stmt(mut wp: Expression, stmt: Statement, data: &mut MirData, debug: bool
 
         Rvalue::BinaryOp(ref binop, ref lval, ref rval) => {
             let lvalue: Expression = gen_expression(lval, data);
-            let rvalue: Expression = gen_expression(rval, data);
+            let mut rvalue: Expression = gen_expression(rval, data);
+            // Floating-point arithmetic never panics. Its remainder is truncated, unlike the one
+            // of IEEE 754 the solver provides.
+            let float = is_float_type(determine_evaluation_type(&lvalue));
+            if float && *binop == BinOp::Rem {
+                rp_unsupported!("Unsupported floating-point remainder: only +, -, *, / and \
+                                comparisons are supported on floats");
+            }
             let op: BinaryOperator = match *binop {
                 BinOp::Add => {
-                    // Add the overflow expression check
-                    wp = overflow::overflow_check(&wp, &var, binop, &lvalue, &rvalue);
+                    if data.arith == Arithmetic::Checked && !float {
+                        // Add the overflow expression check
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
+                    }
                     BinaryOperator::Addition
                 },
                 BinOp::Sub => {
-                    // Add the overflow and underflow expression checks
-                    wp = overflow::overflow_check(&wp, &var, binop, &lvalue, &rvalue);
+                    if data.arith == Arithmetic::Checked && !float {
+                        // Add the overflow and underflow expression checks
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
+                    }
                     BinaryOperator::Subtraction
                 },
                 BinOp::Mul => {
-                    // Add the overflow and underflow expression checks
-                    wp = overflow::overflow_check(&wp, &var, binop, &lvalue, &rvalue);
+                    if data.arith == Arithmetic::Checked && !float {
+                        // Add the overflow and underflow expression checks
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
+                    }
                     BinaryOperator::Multiplication
                 },
                 BinOp::Div => {
                     // Add the overflow and underflow expression checks, if operands are signed
                     if is_signed_type(determine_evaluation_type(&rvalue)) {
-                        wp = overflow::overflow_check(&wp, &var, binop, &lvalue, &rvalue);
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
+                    }
+                    // Add the division by 0 expression check; a float divided by 0 is infinite
+                    // or NaN
+                    if !float {
+                        wp = add_zero_check(&wp, &rvalue, &data.panic_expr);
                     }
-                    // Add the division by 0 expression check
-                    wp = add_zero_check(&wp, &rvalue);
                     BinaryOperator::Division
                 },
                 BinOp::Rem => {
                     // Add the overflow and underflow expression checks, if operands are signed
                     if is_signed_type(determine_evaluation_type(&rvalue)) {
-                        wp = overflow::overflow_check(&wp, &var, binop, &lvalue, &rvalue);
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
                     }
                     // Add the division by 0 expression check
-                    wp = add_zero_check(&wp, &rvalue);
+                    wp = add_zero_check(&wp, &rvalue, &data.panic_expr);
                     BinaryOperator::Modulo
                 },
                 BinOp::BitOr => BinaryOperator::BitwiseOr,
                 BinOp::BitAnd => BinaryOperator::BitwiseAnd,
                 BinOp::BitXor => BinaryOperator::BitwiseXor,
-                BinOp::Shl => BinaryOperator::BitwiseLeftShift,
-                BinOp::Shr => BinaryOperator::BitwiseRightShift,
+                BinOp::Shl => {
+                    if data.arith == Arithmetic::Checked {
+                        // Add the shift amount check
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
+                    } else {
+                        // The shift amount is masked to the width of the value shifted
+                        rvalue = masked_shift_amount(&rvalue, &var);
+                    }
+                    BinaryOperator::BitwiseLeftShift
+                },
+                BinOp::Shr => {
+                    if data.arith == Arithmetic::Checked {
+                        // Add the shift amount check
+                        wp = overflow::overflow_check(&wp,
+                                                      &var,
+                                                      binop,
+                                                      &lvalue,
+                                                      &rvalue,
+                                                      &data.panic_expr);
+                    } else {
+                        // The shift amount is masked to the width of the value shifted
+                        rvalue = masked_shift_amount(&rvalue, &var);
+                    }
+                    BinaryOperator::BitwiseRightShift
+                },
                 BinOp::Lt => BinaryOperator::LessThan,
                 BinOp::Le => BinaryOperator::LessThanOrEqual,
                 BinOp::Gt => BinaryOperator::GreaterThan,
@@ -305,7 +889,13 @@ fn gen_stmt(mut wp: Expression, stmt: Statement, data: &mut MirData, debug: bool
                         UnaryOperator::BitwiseNot
                     }
                 },
-                UnOp::Neg => UnaryOperator::Negation,
+                UnOp::Neg => {
+                    if data.arith == Arithmetic::Checked {
+                        // Add the overflow expression check
+                        wp = overflow::negation_check(&wp, &exp, &data.panic_expr);
+                    }
+                    UnaryOperator::Negation
+                },
             };
             // push the ne new exp onto the expression: Vec<>
             expression.push(Expression::UnaryExpression( UnaryExpressionData {
@@ -315,6 +905,15 @@ fn gen_stmt(mut wp: Expression, stmt: Statement, data: &mut MirData, debug: bool
         },
         //  FIXME: need def
         Rvalue::Use(ref operand) => {
+            if option {
+                let is_some = match *operand {
+                    Operand::Consume(ref l) => {
+                        Expression::VariableMapping(option_flag(&gen_lvalue(l.clone(), data)))
+                    },
+                    Operand::Constant(..) => rp_unsupported!("Unsupported Option constant"),
+                };
+                substitute_state_variable(&mut wp, &option_flag(&var), &is_some);
+            }
             expression.push(gen_expression(operand, data));
         },
         //  FIXME: need def
@@ -330,28 +929,59 @@ fn gen_stmt(mut wp: Expression, stmt: Statement, data: &mut MirData, debug: bool
                         expression.push(e);
                     }
                 },
-                _ => rp_error!("Unsupported aggregate: only tuples are supported"),
+                // Some(x) has the value of x; None has no value
+                AggregateKind::Adt(_, variant, _) if option => {
+                    let is_some = Expression::BooleanLiteral(variant == 1);
+                    substitute_state_variable(&mut wp, &option_flag(&var), &is_some);
+                    if variant == 1 {
+                        expression.push(gen_expression(&vec_operand[0], data));
+                    }
+                },
+                // Wrapping(x) has the value of x
+                AggregateKind::Adt(ref adt_def, _, _)
+                    if format!("{:?}", adt_def).ends_with("num::Wrapping") => {
+                    expression.push(gen_expression(&vec_operand[0], data));
+                },
+                _ => {
+                    rp_unsupported!("Unsupported aggregate: only tuples and Wrapping are supported")
+                },
             }
         },
-        // FIXME: need def
-        // Cast(ref cast_kind, ref cast_operand, ref cast_ty)
-        Rvalue::Cast(..) => {
-            expression.push(Expression::VariableMapping(var.clone()));
+        // Integer and boolean casts truncate or extend the operand to the target type, and casts
+        // to floats round it
+        Rvalue::Cast(CastKind::Misc, ref operand, ref ty) => {
+            let target = match try_string_to_type(ty.to_string().as_str()) {
+                Some(t) if t != Types::Bool => t,
+                _ => rp_unsupported!("Unsupported cast to {}: only integer and floating-point \
+                                     casts are supported", ty),
+            };
+            let e = gen_expression(operand, data);
+            // The compiler leaves the result undefined when it is out of the range of the target
+            if is_float_type(determine_evaluation_type(&e)) && !is_float_type(target.clone()) {
+                rp_unsupported!("Unsupported cast of a floating-point value to {}", ty);
+            }
+            expression.push(Expression::Cast( CastData {
+                e: Box::new(e),
+                ty: target
+            } ));
         },
-        // FIXME: need def
-        // Ref(ref ref_region, ref ref_borrow_kind, ref ref_lvalue) => {
-        Rvalue::Ref(..) => {
-            expression.push(Expression::VariableMapping(var.clone()));
+        Rvalue::Cast(..) => {
+            rp_unsupported!("Unsupported cast: only integer and floating-point casts are supported")
         },
+        // Ref(ref ref_region, ref ref_borrow_kind, ref ref_lvalue)
+        // Only references to supported lvalues are assigned to non-reference lvalues
+        Rvalue::Ref(..) => rp_unsupported!("Unsupported reference: only references to arguments, \
+                                           variables and temporaries of supported types are \
+                                           supported"),
         // Unimplemented Rvalues
-        Rvalue::Box(..) => unimplemented!(),
-        Rvalue::Len(..) => unimplemented!(),
-        _ => unimplemented!(),
+        Rvalue::Box(..) => rp_unsupported!("Unsupported box"),
+        Rvalue::Len(..) => rp_unsupported!("Unsupported length of an array or slice"),
+        _ => rp_unsupported!("Unsupported rvalue"),
     };
 
     // Replace any appearance of var in the weakest precondition with the expression
     for expr in &expression {
-        substitute_variable_with_expression( &mut wp, &var, expr );
+        substitute_state_variable( &mut wp, &var, expr );
     }
     // Prints the new weakest precondition
     if debug {
@@ -360,6 +990,469 @@ fn gen_stmt(mut wp: Expression, stmt: Statement, data: &mut MirData, debug: bool
     return Some(wp);
 }
 
+/// Returns the weakest precondition of a call of the function being verified to itself, which
+/// assumes that the function satisfies its own specification
+///
+/// # Arguments:
+/// * `wp` - The weakest precondition after the call returns
+/// * `contract` - The specification of the function
+/// * `args` - The arguments of the call
+/// * `lvalue` - The lvalue the result of the call is assigned to
+/// * `data` - Contains the `BasicBlockData` and all argument, temp, and variable declarations from
+///            the MIR pass.
+///
+/// # Return Value:
+/// * Returns the weakest precondition before the call
+///
+/// # Remarks:
+/// * wp(x = f(a), Q) => pre(a) AND (forall x. post(a, x) => Q)
+/// * With a measure `#[decreases="m"]`, the call must also satisfy 0 <= m(a) AND m(a) < old(m),
+///   so that the recursion terminates
+///
+fn gen_recursive_call(wp: Expression,
+                      contract: &Contract,
+                      args: &[Operand],
+                      lvalue: Lvalue,
+                      data: &mut MirData)
+                      -> Expression {
+    if contract.params.len() != args.len()
+       || contract.params.iter().any(|p| p.name.starts_with("*")) {
+        rp_error!("Unsupported recursive call to {}: only arguments of integer, bool and char \
+                  types are supported", contract.name);
+    }
+    // Whether a call panics is not described by the postconditions
+    if data.goal == Goal::Postcondition && data.panic_expr != Expression::BooleanLiteral(false) {
+        rp_error!("Unsupported recursive call to {}: a function with a panic condition may not \
+                  call itself", contract.name);
+    }
+    let var = gen_lvalue(lvalue, data);
+    let operands: Vec<Expression> = args.iter().map(|a| gen_argument(a, data)).collect();
+
+    // The parameters are renamed first, so that arguments that refer to other parameters are not
+    // substituted again
+    let instantiate = |e: &Expression| -> Expression {
+        let mut instance = e.clone();
+        let mut placeholders: Vec<VariableMappingData> = Vec::new();
+        for param in contract.params.iter() {
+            let placeholder = VariableMappingData {
+                name: format!("{}!{}", contract.name, param.name),
+                var_type: param.var_type.clone(),
+            };
+            let renamed = Expression::VariableMapping(placeholder.clone());
+            substitute_variable_with_expression(&mut instance, param, &renamed);
+            placeholders.push(placeholder);
+        }
+        for (placeholder, operand) in placeholders.iter().zip(operands.iter()) {
+            substitute_variable_with_expression(&mut instance, placeholder, operand);
+        }
+        instance
+    };
+
+    // The result of the call is any value satisfying the postconditions. old() in them refers to
+    // the arguments of the call.
+    let result = VariableMappingData {
+        name: format!("{}!result", var.name),
+        var_type: var.var_type.clone(),
+    };
+    let mut post = contract.post.clone();
+    remove_old(&mut post);
+    substitute_variable_with_expression(&mut post,
+                                        &VariableMappingData {
+                                            name: "return".to_string(),
+                                            var_type: var.var_type.clone(),
+                                        },
+                                        &Expression::VariableMapping(result.clone()));
+    let mut after = wp;
+    substitute_state_variable(&mut after,
+                              &var,
+                              &Expression::VariableMapping(result.clone()));
+    // The precondition and measure are checked with the postconditions, and assumed otherwise
+    let check = data.goal == Goal::Postcondition;
+    let mut obligations: Vec<Expression> = Vec::new();
+    if check {
+        obligations.push(instantiate(&contract.pre));
+    }
+    let assumption = Expression::Quantifier( QuantifierData {
+        quantifier: Quantifier::Forall,
+        var: result,
+        lower: None,
+        upper: None,
+        body: Box::new(Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::Implication,
+            left: Box::new(instantiate(&post)),
+            right: Box::new(after),
+        } )),
+    } );
+
+    // The measure must be non-negative and decrease from its value on entry
+    if let (true, Some(measure)) = (check, contract.decreases.as_ref()) {
+        let measure_type = determine_evaluation_type(measure);
+        let zero = match typed_integer_literal("0", &measure_type) {
+            Ok(z) => z,
+            Err(e) => rp_error!("{}", e),
+        };
+        let call_measure = instantiate(measure);
+        obligations.push(Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::LessThanOrEqual,
+            left: Box::new(zero),
+            right: Box::new(call_measure.clone()),
+        } ));
+        obligations.push(Expression::BinaryExpression( BinaryExpressionData {
+            op: BinaryOperator::LessThan,
+            left: Box::new(call_measure),
+            right: Box::new(Expression::UnaryExpression( UnaryExpressionData {
+                op: UnaryOperator::Old,
+                e: Box::new(measure.clone()),
+            } )),
+        } ));
+    }
+    obligations.push(assumption);
+    conjunction(obligations)
+}
+
+/// Returns a (possibly) modified weakest precondition based on a call to a function
+///
+/// # Arguments:
+/// * `wp` - The weakest precondition after the call returns
+/// * `name` - The path of the function called
+/// * `external` - Whether the function is defined outside the crate
+/// * `args` - The arguments of the call
+/// * `lvalue` - The lvalue the result of the call is assigned to
+/// * `data` - Contains the `BasicBlockData` and all argument, temp, and variable declarations from
+///            the MIR pass.
+///
+/// # Return Value:
+/// * Returns the weakest precondition before the call
+///
+/// # Remarks:
+/// * Currently supported functions, all from the standard library:
+///     * `wrapping_`, `checked_` and `overflowing_` methods of the integer types for `add`, `sub`,
+///       `mul`, `div`, `rem`, `neg`, `shl` and `shr`
+///     * `saturating_add`, `saturating_sub`, `saturating_mul` and `abs` of the integer types
+///     * `is_nan`, `is_finite` and `is_infinite` of `f32` and `f64`
+///     * the operators of `Wrapping<T>` that correspond to the `wrapping_` methods
+///     * `is_some`, `is_none`, `unwrap`, `expect` and `unwrap_or` of `Option<T>`
+/// * Any other call is unsupported, including one to a function of the crate with the name of a
+///   supported method
+/// * An `Option` is modelled by the value it holds when it is `Some`, and a flag recording whether
+///   it is `Some` (see `option_flag`)
+///
+fn gen_call(mut wp: Expression,
+            name: &str,
+            external: bool,
+            args: &[Operand],
+            lvalue: Lvalue,
+            data: &mut MirData)
+            -> Expression {
+    let (receiver, method) = match library_method(name, external) {
+        Some(m) => m,
+        None => rp_unsupported!("Unsupported call to {}", name),
+    };
+    let option = is_option(&lvalue, data);
+    let var = gen_lvalue(lvalue, data);
+
+    // Methods of Option
+    if receiver == Receiver::Option {
+        let value = gen_argument(&args[0], data);
+        let is_some = match value {
+            Expression::VariableMapping(ref v) => Expression::VariableMapping(option_flag(v)),
+            _ => rp_unsupported!("Unsupported Option argument to {}", method),
+        };
+        let expression = match method.as_str() {
+            "is_some" => is_some,
+            "is_none" => {
+                Expression::UnaryExpression( UnaryExpressionData {
+                    op: UnaryOperator::Not,
+                    e: Box::new(is_some)
+                } )
+            },
+            "unwrap" | "expect" => {
+                // Unwrapping None panics
+                substitute_state_variable(&mut wp, &var, &value);
+                return guard(&wp, is_some, &data.panic_expr);
+            },
+            "unwrap_or" => {
+                Expression::Conditional( ConditionalData {
+                    condition: Box::new(is_some),
+                    then_expression: Box::new(value),
+                    else_expression: Box::new(gen_argument(&args[1], data)),
+                } )
+            },
+            _ => unreachable!(),
+        };
+        substitute_state_variable(&mut wp, &var, &expression);
+        return wp;
+    }
+
+    let operands: Vec<Expression> = args.iter().map(|a| gen_argument(a, data)).collect();
+    if receiver == Receiver::Float {
+        // Classification of f32 and f64 values
+        let expression = Expression::FunctionCall( FunctionCallData {
+            name: method.clone(),
+            args: vec![operands[0].clone()],
+            params: None,
+        } );
+        substitute_state_variable(&mut wp, &var, &expression);
+        return wp;
+    }
+    if method == "abs" && receiver == Receiver::Integer {
+        // abs(MIN) overflows as -MIN does
+        let expression = Expression::FunctionCall( FunctionCallData {
+            name: "abs".to_string(),
+            args: vec![operands[0].clone()],
+            params: None,
+        } );
+        substitute_state_variable(&mut wp, &var, &expression);
+        if data.arith == Arithmetic::Checked {
+            wp = overflow::negation_check(&wp, &operands[0], &data.panic_expr);
+        }
+        return wp;
+    }
+
+    // Integer methods, named <semantics>_<operation>
+    let (semantics, operation) = match method.find('_') {
+        Some(i) => (&method[..i], &method[i + 1..]),
+        None => unreachable!(),
+    };
+    let ty = determine_evaluation_type(&operands[0]);
+    let binop = match operation {
+        "add" => BinOp::Add,
+        "sub" => BinOp::Sub,
+        "mul" => BinOp::Mul,
+        "div" => BinOp::Div,
+        "rem" => BinOp::Rem,
+        "shl" => BinOp::Shl,
+        "shr" => BinOp::Shr,
+        "neg" => {
+            // Negation has one operand
+            let result = Expression::UnaryExpression( UnaryExpressionData {
+                op: UnaryOperator::Negation,
+                e: Box::new(operands[0].clone())
+            } );
+            let no_overflow = overflow::no_negation_overflow(&operands[0]);
+            return assign_result(wp, semantics, &var, option, result, no_overflow, None);
+        },
+        _ => unreachable!(),
+    };
+
+    // The wrapped result of the operation, and whether it overflows
+    let mut rvalue = operands[1].clone();
+    if binop == BinOp::Shl || binop == BinOp::Shr {
+        rvalue = masked_shift_amount(&rvalue, &VariableMappingData {
+            name: var.name.clone(),
+            var_type: ty.clone(),
+        });
+    }
+    let result = Expression::BinaryExpression( BinaryExpressionData {
+        op: match binop {
+            BinOp::Add => BinaryOperator::Addition,
+            BinOp::Sub => BinaryOperator::Subtraction,
+            BinOp::Mul => BinaryOperator::Multiplication,
+            BinOp::Div => BinaryOperator::Division,
+            BinOp::Rem => BinaryOperator::Modulo,
+            BinOp::Shl => BinaryOperator::BitwiseLeftShift,
+            _ => BinaryOperator::BitwiseRightShift,
+        },
+        left: Box::new(operands[0].clone()),
+        right: Box::new(rvalue)
+    } );
+    let mut no_overflow = overflow::no_overflow(&binop, &ty, &operands[0], &operands[1]);
+
+    let saturated = if semantics == "saturating" {
+        match binop {
+            BinOp::Add | BinOp::Sub | BinOp::Mul => {
+                Some(overflow::saturation_bound(&binop, &ty, &operands[0], &operands[1]))
+            },
+            _ => unreachable!(),
+        }
+    } else {
+        None
+    };
+
+    if binop == BinOp::Div || binop == BinOp::Rem {
+        if semantics == "checked" {
+            // Division by 0 gives None
+            no_overflow = Expression::BinaryExpression( BinaryExpressionData {
+                op: BinaryOperator::And,
+                left: Box::new(nonzero(&operands[1])),
+                right: Box::new(no_overflow)
+            } );
+        } else {
+            // Division by 0 panics
+            wp = assign_result(wp, semantics, &var, option, result, no_overflow, saturated);
+            return add_zero_check(&wp, &operands[1], &data.panic_expr);
+        }
+    }
+    assign_result(wp, semantics, &var, option, result, no_overflow, saturated)
+}
+
+// The types of the standard library whose methods gen_call supports
+#[derive(PartialEq)]
+enum Receiver {
+    Integer,
+    Float,
+    Option,
+    Wrapping,
+}
+
+// Returns the type and name of a supported method of the standard library, from the path of a call
+// to it, such as i32::wrapping_add, <i32>::abs, <std::option::Option<u8>>::unwrap or
+// <std::num::Wrapping<u8> as std::ops::Add>::add. The operator methods of Wrapping<T> are named
+// after the wrapping_ methods of T they behave as.
+fn library_method(name: &str, external: bool) -> Option<(Receiver, String)> {
+    if !external {
+        return None;
+    }
+    let (path, method) = match name.rfind("::") {
+        Some(i) => (&name[..i], &name[i + 2..]),
+        None => return None,
+    };
+    let operation = |m: &str| -> bool {
+        ["add", "sub", "mul", "div", "rem", "neg", "shl", "shr"].contains(&m)
+    };
+    let in_library = |ty: &str| -> bool {
+        ["std::", "<std::", "core::", "<core::"].iter().any(|prefix| {
+            path.starts_with((prefix.to_string() + ty).as_str())
+        })
+    };
+
+    // The inherent methods of a primitive type are named after it, with or without brackets
+    let primitive = path.trim_left_matches('<').trim_right_matches('>');
+    let primitive_form = path == primitive || path == format!("<{}>", primitive);
+    let receiver = if primitive_form && is_integer_type(primitive) {
+        Receiver::Integer
+    } else if primitive_form && (primitive == "f32" || primitive == "f64") {
+        Receiver::Float
+    } else if in_library("option::Option<") {
+        Receiver::Option
+    } else if in_library("num::Wrapping<") {
+        Receiver::Wrapping
+    } else {
+        return None;
+    };
+
+    let supported = match receiver {
+        Receiver::Integer => {
+            method == "abs" || match method.find('_') {
+                Some(i) => {
+                    let (semantics, op) = (&method[..i], &method[i + 1..]);
+                    match semantics {
+                        "wrapping" | "checked" | "overflowing" => operation(op),
+                        "saturating" => op == "add" || op == "sub" || op == "mul",
+                        _ => false,
+                    }
+                },
+                None => false,
+            }
+        },
+        Receiver::Float => method == "is_nan" || method == "is_finite" || method == "is_infinite",
+        Receiver::Option => {
+            ["is_some", "is_none", "unwrap", "expect", "unwrap_or"].contains(&method)
+        },
+        Receiver::Wrapping => operation(method),
+    };
+    if !supported {
+        return None;
+    }
+    if receiver == Receiver::Wrapping {
+        // The operators of Wrapping<T> behave as the wrapping_ methods of T
+        return Some((Receiver::Integer, "wrapping_".to_string() + method));
+    }
+    Some((receiver, method.to_string()))
+}
+
+// Checks whether a type name is that of a primitive integer type
+fn is_integer_type(name: &str) -> bool {
+    ["i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize"]
+        .contains(&name)
+}
+
+// Substitutes the result of an integer method into the weakest precondition after the call. The
+// method returns the wrapped result, the result if it does not overflow (checked_), the result or
+// its saturation bound (saturating_), or the wrapped result and whether it overflows
+// (overflowing_).
+fn assign_result(mut wp: Expression,
+                 semantics: &str,
+                 var: &VariableMappingData,
+                 option: bool,
+                 result: Expression,
+                 no_overflow: Expression,
+                 saturated: Option<Expression>)
+                 -> Expression {
+    match semantics {
+        "wrapping" => substitute_state_variable(&mut wp, var, &result),
+        "checked" if option => {
+            substitute_state_variable(&mut wp, var, &result);
+            substitute_state_variable(&mut wp, &option_flag(var), &no_overflow);
+        },
+        "saturating" if saturated.is_some() => {
+            let expression = Expression::Conditional( ConditionalData {
+                condition: Box::new(no_overflow),
+                then_expression: Box::new(result),
+                else_expression: Box::new(saturated.unwrap()),
+            } );
+            substitute_state_variable(&mut wp, var, &expression);
+        },
+        "overflowing" => {
+            // The result is a tuple
+            let value = VariableMappingData {
+                name: var.name.clone() + ".0",
+                var_type: var.var_type.clone(),
+            };
+            let overflowed = VariableMappingData {
+                name: var.name.clone() + ".1",
+                var_type: Types::Bool,
+            };
+            substitute_state_variable(&mut wp, &value, &result);
+            substitute_state_variable(&mut wp, &overflowed, &Expression::UnaryExpression(
+                UnaryExpressionData {
+                    op: UnaryOperator::Not,
+                    e: Box::new(no_overflow),
+                }
+            ));
+        },
+        _ => rp_unsupported!("Unsupported use of the result of a {} method", semantics),
+    }
+    wp
+}
+
+// Generates an Expression for an argument of a call. A reference argument is replaced by the
+// value it refers to.
+fn gen_argument(operand: &Operand, data: &mut MirData) -> Expression {
+    if let Operand::Consume(ref l) = *operand {
+        if let Some(v) = gen_reference_target(l, data) {
+            return Expression::VariableMapping(v);
+        }
+    }
+    gen_expression(operand, data)
+}
+
+/// Masks a shift amount to the width of the value shifted, as shifts do when they wrap around
+///
+/// # Arguments:
+/// * `amount` - The shift amount
+/// * `var` - The variable the result of the shift is assigned to
+///
+/// # Return Value:
+/// * Returns `amount & (width - 1)`, in the type of `amount`
+///
+fn masked_shift_amount(amount: &Expression, var: &VariableMappingData) -> Expression {
+    let size = match type_size(var.var_type.clone()) {
+        Some(s) => s,
+        None => rp_unsupported!("Unsupported shift of type {}", var.var_type),
+    };
+    let mask = match typed_integer_literal(&(size - 1).to_string(),
+                                           &determine_evaluation_type(amount)) {
+        Ok(m) => m,
+        Err(e) => rp_error!("{}", e),
+    };
+    Expression::BinaryExpression( BinaryExpressionData {
+        op: BinaryOperator::BitwiseAnd,
+        left: Box::new(amount.clone()),
+        right: Box::new(mask)
+    } )
+}
+
 /// Returns the type of an operand as a `Types`
 ///
 /// # Arguments:
@@ -380,7 +1473,7 @@ fn gen_ty(operand: &Operand, data: &mut MirData) -> Types {
                 Lvalue::Temp(ref temp) => data.temp_data[temp.index()].ty.to_string(),
                 // Local variable
                 Lvalue::Var(ref var) => data.var_data[var.index()].ty.to_string(),
-                _ => unimplemented!(),
+                _ => rp_unsupported!("Unsupported operand"),
             }
         },
     };
@@ -394,6 +1487,7 @@ fn gen_ty(operand: &Operand, data: &mut MirData) -> Types {
 /// # Arguments:
 /// * `wp` - The current weakest precondition that the "div by 0" is to be "And"ed to
 /// * `exp` - The expression to check to make sure it is not divided by 0
+/// * `panic_expr` - The weakest precondition if the check fails and the function panics
 ///
 /// # Return Value:
 /// * Returns the modified weakest precondition with "div by 0" Expression "And"ed
@@ -401,7 +1495,13 @@ fn gen_ty(operand: &Operand, data: &mut MirData) -> Types {
 /// # Remarks:
 /// * Currently supported `ConstInt`: `i8`, `i16`, `i32`, `i64`, `u8`, `u16`, `u32`, `u64`
 ///
-fn add_zero_check(wp: &Expression, exp: &Expression) -> Expression {
+fn add_zero_check(wp: &Expression, exp: &Expression, panic_expr: &Expression) -> Expression {
+    // And the weakest precondtion and the zero check
+    guard(wp, nonzero(exp), panic_expr)
+}
+
+// Returns an Expression that is true when exp is not 0
+fn nonzero(exp: &Expression) -> Expression {
     let zero;
     if is_signed_type(determine_evaluation_type(exp)) {
         zero = Expression::SignedBitVector( SignedBitVectorData {
@@ -411,6 +1511,7 @@ fn add_zero_check(wp: &Expression, exp: &Expression) -> Expression {
                 Types::I16 => 16,
                 Types::I32 => 32,
                 Types::I64 => 64,
+                Types::I128 => 128,
                 _ => rp_error!("Unimplemented checkeddAdd right-hand operand type"),
             },
             value: 0
@@ -423,6 +1524,7 @@ fn add_zero_check(wp: &Expression, exp: &Expression) -> Expression {
                 Types::U16 => 16,
                 Types::U32 => 32,
                 Types::U64 => 64,
+                Types::U128 => 128,
                 _ => rp_error!("Unimplemented checkeddAdd right-hand operand type"),
             },
             value: 0
@@ -430,19 +1532,196 @@ fn add_zero_check(wp: &Expression, exp: &Expression) -> Expression {
     }
 
     Expression::BinaryExpression( BinaryExpressionData{
-        // And the weakest precondtion and the zero check
+        op: BinaryOperator::NotEqual,
+        // The expression to be checked
+        left: Box::new(exp.clone()),
+        // Need to set appropriate type with value of 0
+        right: Box::new(zero),
+    })
+}
+
+/// Adds a runtime check, which panics if it fails, to a weakest precondition
+///
+/// # Arguments:
+/// * `wp` - The weakest precondition if the check succeeds
+/// * `check` - The condition under which the check succeeds
+/// * `panic_expr` - The weakest precondition if the check fails and the function panics
+///
+/// # Return Value:
+/// * Returns `(check AND wp) OR (NOT check AND panic_expr)`
+///
+/// # Remarks:
+/// * When `panic_expr` is `false`, as it is unless a panic condition is being checked, this is
+///   simply `wp AND check`.
+///
+fn guard(wp: &Expression, check: Expression, panic_expr: &Expression) -> Expression {
+    let checked = Expression::BinaryExpression( BinaryExpressionData{
         op: BinaryOperator::And,
         left: Box::new(wp.clone()),
+        right: Box::new(check.clone()),
+    });
+    if *panic_expr == Expression::BooleanLiteral(false) {
+        return checked;
+    }
+    Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::Or,
+        left: Box::new(checked),
         right: Box::new(Expression::BinaryExpression( BinaryExpressionData{
-            op: BinaryOperator::NotEqual,
-            // The expression to be checked
-            left: Box::new(exp.clone()),
-            // Need to set appropriate type with value of 0
-            right: Box::new(zero),
-        }))
+            op: BinaryOperator::And,
+            left: Box::new(Expression::UnaryExpression( UnaryExpressionData{
+                op: UnaryOperator::Not,
+                e: Box::new(check),
+            })),
+            right: Box::new(panic_expr.clone()),
+        })),
     })
 }
 
+/// Records which lvalue each reference created in the function body refers to
+///
+/// # Arguments:
+/// * `data` - Contains the `BasicBlockData` and all argument, temp, and variable declarations from
+///            the MIR pass.
+///
+/// # Remarks:
+/// * A temp or variable assigned `&x`, `&mut x` or a copy of another reference is recorded in
+///   `data.references`, so that `*tmp` can be treated as `x` by `gen_lvalue`.
+/// * Must be called before `gen`, since the weakest precondition is generated in reverse order and
+///   sees each dereference before the reference is created.
+///
+pub fn gen_references(data: &mut MirData) {
+    for index in 0..data.block_data.len() {
+        let block = data.block_data[index];
+        for stmt in block.statements.iter() {
+            match stmt.kind {
+                StatementKind::Assign(ref lval, ref rval) => {
+                    // The lvalue the reference points to, if this creates a reference
+                    let referent = match *rval {
+                        Rvalue::Ref(_, _, ref l) => gen_referent(l, data),
+                        Rvalue::Use(Operand::Consume(ref l)) => gen_reference_target(l, data),
+                        _ => None,
+                    };
+                    if let Some(r) = referent {
+                        let name = match *lval {
+                            Lvalue::Temp(ref temp) => format!("tmp{}", temp.index()),
+                            Lvalue::Var(ref var) => format!("var{}", var.index()),
+                            _ => continue,
+                        };
+                        // A reference must refer to the same lvalue everywhere it is used
+                        if let Some(previous) = data.references.get(&name) {
+                            if *previous != r {
+                                rp_unsupported!("Unsupported reference: {} refers to both {} \
+                                                and {}", name, previous.name, r.name);
+                            }
+                        }
+                        data.references.insert(name, r);
+                    }
+                },
+            }
+        }
+    }
+}
+
+// Returns the variable mapping of a borrowed lvalue, if its value can be modelled
+fn gen_referent(lvalue: &Lvalue, data: &mut MirData) -> Option<VariableMappingData> {
+    let type_string = match *lvalue {
+        Lvalue::Arg(ref arg) => data.arg_data[arg.index()].ty.to_string(),
+        Lvalue::Temp(ref temp) => data.temp_data[temp.index()].ty.to_string(),
+        Lvalue::Var(ref var) => data.var_data[var.index()].ty.to_string(),
+        // Reborrowing a reference, e.g. &mut *x
+        Lvalue::Projection(ref pro) => {
+            if let ProjectionElem::Deref = pro.as_ref().elem {
+                return gen_reference_target(&pro.as_ref().base, data);
+            }
+            return None;
+        },
+        _ => return None,
+    };
+
+    let modelled = try_string_to_type(type_string.as_str()).is_some()
+                   || option_type(type_string.as_str()).is_some();
+    if modelled {
+        Some(gen_lvalue(lvalue.clone(), data))
+    } else {
+        None
+    }
+}
+
+// Returns the variable mapping that a reference points to, if it is a reference argument or a
+// reference recorded by gen_references
+fn gen_reference_target(lvalue: &Lvalue, data: &mut MirData) -> Option<VariableMappingData> {
+    match *lvalue {
+        Lvalue::Arg(ref arg) => {
+            match data.arg_data[arg.index()].ty.sty {
+                TypeVariants::TyRef(_, ref tm) => {
+                    match try_string_to_type(tm.ty.to_string().as_str()) {
+                        Some(t) => {
+                            Some(VariableMappingData {
+                                name: "*".to_string()
+                                      + data.arg_data[arg.index()].debug_name.as_str(),
+                                var_type: t
+                            })
+                        },
+                        None => None,
+                    }
+                },
+                _ => None,
+            }
+        },
+        Lvalue::Temp(ref temp) => data.references.get(&format!("tmp{}", temp.index())).cloned(),
+        Lvalue::Var(ref var) => data.references.get(&format!("var{}", var.index())).cloned(),
+        _ => None,
+    }
+}
+
+// Checks if an lvalue is an argument, temp or variable of type Wrapping<T>
+fn is_wrapping(lvalue: &Lvalue, data: &MirData) -> bool {
+    let type_string = match *lvalue {
+        Lvalue::Arg(ref arg) => data.arg_data[arg.index()].ty.to_string(),
+        Lvalue::Temp(ref temp) => data.temp_data[temp.index()].ty.to_string(),
+        Lvalue::Var(ref var) => data.var_data[var.index()].ty.to_string(),
+        _ => return false,
+    };
+    wrapped_type(type_string.as_str()).is_some()
+}
+
+// Checks if an lvalue is a temp or variable of type Option<T>
+fn is_option(lvalue: &Lvalue, data: &MirData) -> bool {
+    let type_string = match *lvalue {
+        Lvalue::Temp(ref temp) => data.temp_data[temp.index()].ty.to_string(),
+        Lvalue::Var(ref var) => data.var_data[var.index()].ty.to_string(),
+        _ => return false,
+    };
+    option_type(type_string.as_str()).is_some()
+}
+
+// Returns the type held by an Option<T>, given the text of the type
+fn option_type(s: &str) -> Option<&str> {
+    for prefix in ["std::option::Option<", "core::option::Option<"].iter() {
+        if s.starts_with(*prefix) && s.ends_with(">") {
+            return Some(&s[prefix.len()..s.len() - 1]);
+        }
+    }
+    None
+}
+
+// Returns the flag recording whether the Option named by var is Some
+fn option_flag(var: &VariableMappingData) -> VariableMappingData {
+    VariableMappingData {
+        name: var.name.clone() + ".is_some",
+        var_type: Types::Bool,
+    }
+}
+
+// Checks if an lvalue is a temp or variable holding a reference recorded by gen_references
+fn is_reference(lvalue: &Lvalue, data: &MirData) -> bool {
+    match *lvalue {
+        Lvalue::Temp(ref temp) => data.references.contains_key(&format!("tmp{}", temp.index())),
+        Lvalue::Var(ref var) => data.references.contains_key(&format!("var{}", var.index())),
+        _ => false,
+    }
+}
+
 /// Generates an appropriate variable mapping based on whatever variable, temp, or field is found
 ///
 /// # Arguments:
@@ -474,6 +1753,10 @@ fn gen_lvalue(lvalue: Lvalue, data: &mut MirData) -> VariableMappingData {
                     ty = t[0].to_string();
                 }
             }
+            // An Option is named by the value it holds when it is Some
+            if let Some(t) = option_type(ty.as_str()).map(|t| t.to_string()) {
+                ty = t;
+            }
             VariableMappingData{
                 name: "tmp".to_string() + temp.index().to_string().as_str(),
                 var_type: string_to_type(ty)
@@ -482,9 +1765,19 @@ fn gen_lvalue(lvalue: Lvalue, data: &mut MirData) -> VariableMappingData {
         // Local variable
         Lvalue::Var(ref var) => {
             // Find the name and type in the declaration
+            let mut ty = data.var_data[var.index()].ty.clone().to_string();
+            if let TypeVariants::TyTuple(t) = data.var_data[var.index()].ty.sty {
+                if t.len() > 0 {
+                    ty = t[0].to_string();
+                }
+            }
+            // An Option is named by the value it holds when it is Some
+            if let Some(t) = option_type(ty.as_str()).map(|t| t.to_string()) {
+                ty = t;
+            }
             VariableMappingData{
                 name: "var".to_string() + var.index().to_string().as_str(),
-                var_type: string_to_type(data.var_data[var.index()].ty.clone().to_string())
+                var_type: string_to_type(ty)
             }
         },
         // The returned value
@@ -496,14 +1789,36 @@ fn gen_lvalue(lvalue: Lvalue, data: &mut MirData) -> VariableMappingData {
         },
         // (Most likely) a field of a tuple from a checked operation
         Lvalue::Projection(pro) => {
+            // A dereference; the value behind a reference argument is named "*<argument>"
+            if let ProjectionElem::Deref = pro.as_ref().elem {
+                match gen_reference_target(&pro.as_ref().base, data) {
+                    Some(v) => return v,
+                    None => rp_unsupported!("Unsupported dereference: only references to supported \
+                                            types are supported"),
+                }
+            }
+
+            // A Wrapping<T> has the same value as the T it wraps
+            if is_wrapping(&pro.as_ref().base, data) {
+                return gen_lvalue(pro.as_ref().base.clone(), data);
+            }
+
+            // The value held by Some, (o as Some).0, is named by the Option
+            if let Lvalue::Projection(ref downcast) = pro.as_ref().base {
+                if let ProjectionElem::Downcast(..) = downcast.as_ref().elem {
+                    if is_option(&downcast.as_ref().base, data) {
+                        return gen_lvalue(downcast.as_ref().base.clone(), data);
+                    }
+                }
+            }
 
             // Get the index
             let index: String = match pro.as_ref().elem.clone() {
                 // Index(ref o)
-                ProjectionElem::Index(_) => unimplemented!(),
+                ProjectionElem::Index(_) => rp_unsupported!("Unsupported indexing"),
                 // Field(ref field, ref ty)
                 ProjectionElem::Field(ref field, _) => (field.index() as i32).to_string(),
-                _ => unimplemented!(),
+                _ => rp_unsupported!("Unsupported projection"),
             };
 
             // Get the name of the variable being projected
@@ -522,9 +1837,10 @@ fn gen_lvalue(lvalue: Lvalue, data: &mut MirData) -> VariableMappingData {
                     // Return "temp<index>"
                     lvalue_name = "tmp".to_string() + temp.index().to_string().as_str();
 
+                    let i = index.parse::<usize>().unwrap();
                     match data.temp_data[temp.index()].ty.sty {
-                        TypeVariants::TyTuple(t) => lvalue_type_string = t[0].to_string(),
-                        _ => unimplemented!(),
+                        TypeVariants::TyTuple(t) => lvalue_type_string = t[i].to_string(),
+                        _ => rp_unsupported!("Unsupported field of a non-tuple"),
                     }
                 },
                 // Local variable
@@ -535,15 +1851,15 @@ fn gen_lvalue(lvalue: Lvalue, data: &mut MirData) -> VariableMappingData {
 
                     match data.var_data[var.index()].ty.sty {
                         TypeVariants::TyTuple(t) => lvalue_type_string = t[i].to_string(),
-                        _ => unimplemented!(),
+                        _ => rp_unsupported!("Unsupported field of a non-tuple"),
                     }
                 },
                 // Unimplemented Lvalue
-                Lvalue::ReturnPointer => unimplemented!(),
+                Lvalue::ReturnPointer => rp_unsupported!("Unsupported field of the returned value"),
                 // Static(ref stat)
-                Lvalue::Static(_) => unimplemented!(),
+                Lvalue::Static(_) => rp_unsupported!("Unsupported static"),
                 // Multiply-nested projection
-                Lvalue::Projection(_) => unimplemented!(),
+                Lvalue::Projection(_) => rp_unsupported!("Unsupported nested projection"),
             };
 
             // Get the index
@@ -552,8 +1868,8 @@ fn gen_lvalue(lvalue: Lvalue, data: &mut MirData) -> VariableMappingData {
                 // Field(ref field, ref ty)
                 ProjectionElem::Field(ref field, _) => (field.index() as i32).to_string(),
                 // Index(ref o)
-                ProjectionElem::Index(_) => unimplemented!(),
-                _ => unimplemented!(),
+                ProjectionElem::Index(_) => rp_unsupported!("Unsupported indexing"),
+                _ => rp_unsupported!("Unsupported projection"),
             };
 
             let lvalue_type: Types = string_to_type(lvalue_type_string);
@@ -561,7 +1877,7 @@ fn gen_lvalue(lvalue: Lvalue, data: &mut MirData) -> VariableMappingData {
             // Get the index int from index_operand, then stick it in the VariableMappingData
             VariableMappingData{ name: lvalue_name + "." + index.as_str(), var_type: lvalue_type }
         },
-        _=> unimplemented!(),
+        _=> rp_unsupported!("Unsupported static"),
     }
 }
 
@@ -577,7 +1893,8 @@ fn gen_lvalue(lvalue: Lvalue, data: &mut MirData) -> VariableMappingData {
 /// * Returns a new expression generated from an operand
 ///
 /// # Remarks:
-/// * Current supported types: `i8`, `i16`, `i32`, `i64`, `u8`, `u16`, `u32`, `u64`, `bool`
+/// * Current supported types: `i8`, `i16`, `i32`, `i64`, `isize`, `u8`, `u16`, `u32`, `u64`,
+///   `usize`, `bool`, `char`, `f32`, `f64`
 ///
 fn gen_expression(operand: &Operand, data: &mut MirData) -> Expression {
     match *operand {
@@ -593,6 +1910,23 @@ fn gen_expression(operand: &Operand, data: &mut MirData) -> Expression {
                         ConstVal::Bool(ref const_bool) => {
                             Expression::BooleanLiteral(*const_bool)
                         }
+                        ConstVal::Char(ref const_char) => {
+                            Expression::CharLiteral(*const_char)
+                        }
+                        ConstVal::Float(f) => {
+                            // The constant is held as an f64 whatever its type
+                            if c.ty.to_string() == "f32" {
+                                Expression::FloatLiteral( FloatLiteralData {
+                                    size: 32,
+                                    bits: unsafe { mem::transmute::<f32, u32>(f as f32) } as u64
+                                } )
+                            } else {
+                                Expression::FloatLiteral( FloatLiteralData {
+                                    size: 64,
+                                    bits: unsafe { mem::transmute::<f64, u64>(f) }
+                                } )
+                            }
+                        }
                         ConstVal::Integral(ref const_int) => {
                             match *const_int {
                                 ConstInt::I8(i) => {
@@ -643,17 +1977,42 @@ fn gen_expression(operand: &Operand, data: &mut MirData) -> Expression {
                                         value: u as u64
                                     } )
                                 },
-                                _ => unimplemented!(),
+                                ConstInt::Isize(i) => {
+                                    let value: i64 = match i {
+                                        ConstIsize::Is16(i) => i as i64,
+                                        ConstIsize::Is32(i) => i as i64,
+                                        ConstIsize::Is64(i) => i,
+                                    };
+                                    pointer_sized_literal(value.to_string(), &Types::Isize)
+                                },
+                                ConstInt::Usize(u) => {
+                                    let value: u64 = match u {
+                                        ConstUsize::Us16(u) => u as u64,
+                                        ConstUsize::Us32(u) => u as u64,
+                                        ConstUsize::Us64(u) => u,
+                                    };
+                                    pointer_sized_literal(value.to_string(), &Types::Usize)
+                                },
+                                _ => rp_unsupported!("Unsupported integer literal"),
                             }
                         },
-                        _ => unimplemented!(),
+                        _ => rp_unsupported!("Unsupported literal"),
                     }
                 },
                 // Item {ref def_id, ref substs}
-                Literal::Item {..} => unimplemented!(),
+                Literal::Item {..} => rp_unsupported!("Unsupported constant item"),
                 // Promoted {ref index}
-                Literal::Promoted {..} => unimplemented!(),
+                Literal::Promoted {..} => rp_unsupported!("Unsupported promoted constant"),
             }
         },
     }
 }
+
+// Returns a literal of isize or usize. The width verified for may differ from that of the
+// compilation target, in which case the constant may be out of range.
+fn pointer_sized_literal(value: String, ty: &Types) -> Expression {
+    match typed_integer_literal(value.as_str(), ty) {
+        Ok(e) => e,
+        Err(e) => rp_error!("{}", e),
+    }
+}
@@ -17,28 +17,126 @@ use rustc::mir::repr::*;
 
 
 /// Routes to appropriate overflow check (signed / unsigned)
-// One catch-all function for overflow checking.
+// One catch-all function for overflow checking. If the check fails, the weakest precondition is
+// panic_expr.
 pub fn overflow_check(wp: &Expression,
                       var: &VariableMappingData,
                       binop: &BinOp,
                       lvalue: &Expression,
-                      rvalue: &Expression)
+                      rvalue: &Expression,
+                      panic_expr: &Expression)
                       -> Expression {
-    Expression::BinaryExpression( BinaryExpressionData {
-        op: BinaryOperator::And,
-        left: Box::new(wp.clone()),
-        right: Box::new(
-            match var.var_type {
-                Types::I8 => signed_overflow(binop, 8u8, lvalue, rvalue),
-                Types::I16 => signed_overflow(binop, 16u8, lvalue, rvalue),
-                Types::I32 => signed_overflow(binop, 32u8, lvalue, rvalue),
-                Types::I64 => signed_overflow(binop, 64u8, lvalue, rvalue),
-                Types::U8 | Types::U16 | Types::U32 | Types::U64 => {
-                    unsigned_overflow(binop, lvalue, rvalue)
-                },
-                _ => panic!("Unsupported return type of binary operation: {}", var.var_type),
-            }
-        ),
+    super::guard(wp, no_overflow(binop, &var.var_type, lvalue, rvalue), panic_expr)
+}
+
+/// Creates an Expression that is true when the binary operation on lvalue and rvalue, whose result
+/// has type "ty", does not overflow
+pub fn no_overflow(binop: &BinOp, ty: &Types, lvalue: &Expression, rvalue: &Expression)
+                   -> Expression {
+    match *ty {
+        Types::I8 => signed_overflow(binop, 8u8, lvalue, rvalue),
+        Types::I16 => signed_overflow(binop, 16u8, lvalue, rvalue),
+        Types::I32 => signed_overflow(binop, 32u8, lvalue, rvalue),
+        Types::I64 => signed_overflow(binop, 64u8, lvalue, rvalue),
+        Types::I128 => signed_overflow(binop, 128u8, lvalue, rvalue),
+        // Unsigned division cannot overflow
+        Types::U8 | Types::U16 | Types::U32 | Types::U64 | Types::U128
+            if *binop == BinOp::Div || *binop == BinOp::Rem => Expression::BooleanLiteral(true),
+        Types::U8 => unsigned_overflow(binop, 8u8, lvalue, rvalue),
+        Types::U16 => unsigned_overflow(binop, 16u8, lvalue, rvalue),
+        Types::U32 => unsigned_overflow(binop, 32u8, lvalue, rvalue),
+        Types::U64 => unsigned_overflow(binop, 64u8, lvalue, rvalue),
+        Types::U128 => unsigned_overflow(binop, 128u8, lvalue, rvalue),
+        Types::Isize | Types::Usize => {
+            no_overflow(binop, &concrete_type(ty.clone()), lvalue, rvalue)
+        },
+        _ => panic!("Unsupported return type of binary operation: {}", ty),
+    }
+}
+
+/// Adds an overflow check for the negation of an expression (signed / unsigned)
+// If the check fails, the weakest precondition is panic_expr. Unsigned values cannot be negated
+// with the - operator, so only signed negation is checked.
+pub fn negation_check(wp: &Expression, e: &Expression, panic_expr: &Expression) -> Expression {
+    if is_signed_type(determine_evaluation_type(e)) {
+        super::guard(wp, no_negation_overflow(e), panic_expr)
+    } else {
+        wp.clone()
+    }
+}
+
+/// Creates an Expression that is true when the negation of e does not overflow
+// Signed: e != MIN. Unsigned: e == 0.
+pub fn no_negation_overflow(e: &Expression) -> Expression {
+    let ty = determine_evaluation_type(e);
+    match type_size(ty.clone()) {
+        Some(size) if is_signed_type(ty.clone()) => signed_neg(size, e),
+        Some(_) => {
+            Expression::BinaryExpression( BinaryExpressionData{
+                op: BinaryOperator::Equal,
+                left: Box::new(e.clone()),
+                right: Box::new(min_value(&ty)),
+            })
+        },
+        None => panic!("Unsupported type of negation: {}", ty),
+    }
+}
+
+/// Creates an Expression for the value a saturating binary operation on lvalue and rvalue, whose
+/// result has type "ty", has when it overflows
+///
+/// The following psuedocode provides a logically equivalent version of what is produced
+///
+/// ```psuedo
+/// Unsigned: Add, Mul => MAX; Sub => 0
+/// Signed:
+///   Add => If rvalue >= 0 then MAX else MIN
+///   Sub => If rvalue < 0 then MAX else MIN
+///   Mul => If (lvalue < 0) == (rvalue < 0) then MAX else MIN
+/// ```
+pub fn saturation_bound(binop: &BinOp, ty: &Types, lvalue: &Expression, rvalue: &Expression)
+                        -> Expression {
+    if !is_signed_type(ty.clone()) {
+        return match *binop {
+            BinOp::Sub => min_value(ty),
+            _ => max_value(ty),
+        };
+    }
+
+    let size = type_size(ty.clone()).unwrap();
+    let negative = |e: &Expression| {
+        Expression::BinaryExpression( BinaryExpressionData{
+            op: BinaryOperator::LessThan,
+            left: Box::new(e.clone()),
+            right: Box::new(
+                Expression::SignedBitVector( SignedBitVectorData {
+                    size: size,
+                    value: 0i64,
+                })
+            ),
+        })
+    };
+    let towards_max = match *binop {
+        BinOp::Add => {
+            Expression::UnaryExpression( UnaryExpressionData{
+                op: UnaryOperator::Not,
+                e: Box::new(negative(rvalue)),
+            })
+        },
+        BinOp::Sub => negative(rvalue),
+        BinOp::Mul => {
+            Expression::BinaryExpression( BinaryExpressionData{
+                op: BinaryOperator::BiImplication,
+                left: Box::new(negative(lvalue)),
+                right: Box::new(negative(rvalue)),
+            })
+        },
+        _ => panic!("Unsupported saturating operation: {:?}", binop),
+    };
+    Expression::Conditional( ConditionalData {
+        condition: Box::new(towards_max),
+        then_expression: Box::new(max_value(ty)),
+        else_expression: Box::new(min_value(ty)),
     })
 }
 
@@ -52,8 +150,8 @@ fn signed_overflow(binop: &BinOp, size: u8, lvalue: &Expression, rvalue: &Expres
         BinOp::Sub => signed_sub(size, lvalue, rvalue),
         BinOp::Div => signed_div(size, lvalue, rvalue),
         BinOp::Rem => signed_div(size, lvalue, rvalue),
-        BinOp::Shl => unimplemented!(),
-        BinOp::Shr => unimplemented!(),
+        BinOp::Shl => shift_overflow(size, rvalue),
+        BinOp::Shr => shift_overflow(size, rvalue),
         BinOp::BitOr => unimplemented!(),
         BinOp::BitAnd => unimplemented!(),
         BinOp::BitXor => unimplemented!(),
@@ -423,18 +521,7 @@ fn signed_div(size: u8, lvalue: &Expression, rvalue: &Expression) -> Expression
             Expression::BinaryExpression( BinaryExpressionData{
                 op: BinaryOperator::Equal,
                 left: Box::new(lvalue.clone()),
-                right: Box::new(
-                    Expression::SignedBitVector( SignedBitVectorData{
-                        size: size,
-                        value: match size {
-                            8u8 => i8::min_value() as i64,
-                            16u8 => i16::min_value() as i64,
-                            32u8 => i32::min_value() as i64,
-                            64u8 => i64::min_value() as i64,
-                            _ => panic!("unsupported integer type"),
-                        },
-                    })
-                ),
+                right: Box::new(signed_min(size)),
             })
         ),
         right: Box::new(
@@ -479,17 +566,82 @@ fn signed_div(size: u8, lvalue: &Expression, rvalue: &Expression) -> Expression
     })
 }
 
+/// Creates an Expression checking that shifting a bitvector of length "size" by rvalue does not
+/// overflow. The shift amount may have any integer type, not only that of the value shifted.
+///
+/// The following psuedocode provides a logically equivalent version of what is produced
+/// (false is returned if overflow has occurred, true otherwise)
+///
+/// ```psuedo
+/// rvalue >= 0 && rvalue < size
+/// ```
+///
+/// The first comparison is left out if rvalue is unsigned, and the second if its type cannot hold
+/// "size".
+fn shift_overflow(size: u8, rvalue: &Expression) -> Expression {
+    let r_type = determine_evaluation_type(rvalue);
+    let below_size = match typed_integer_literal(&size.to_string(), &r_type) {
+        Ok(bound) => {
+            Expression::BinaryExpression( BinaryExpressionData{
+                op: BinaryOperator::LessThan,
+                left: Box::new(rvalue.clone()),
+                right: Box::new(bound),
+            })
+        },
+        // Every value of the type is less than size
+        Err(_) => Expression::BooleanLiteral(true),
+    };
+    if !is_signed_type(r_type.clone()) {
+        return below_size;
+    }
+
+    Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::And,
+        left: Box::new(
+            Expression::BinaryExpression( BinaryExpressionData{
+                op: BinaryOperator::GreaterThanOrEqual,
+                left: Box::new(rvalue.clone()),
+                right: Box::new(
+                    Expression::SignedBitVector( SignedBitVectorData {
+                        size: type_size(r_type).unwrap(),
+                        value: 0i64,
+                    })
+                ),
+            })
+        ),
+        right: Box::new(below_size),
+    })
+}
+
+/// Creates an Expression checking that the negation of e, a bitvector of length "size", does not
+/// overflow
+///
+/// The following psuedocode provides a logically equivalent version of what is produced
+/// (false is returned if overflow has occurred, true otherwise)
+///
+/// ```psuedo
+/// e != MIN
+/// ```
+fn signed_neg(size: u8, e: &Expression) -> Expression {
+    Expression::BinaryExpression( BinaryExpressionData{
+        op: BinaryOperator::NotEqual,
+        left: Box::new(e.clone()),
+        right: Box::new(signed_min(size)),
+    })
+}
+
 /// Routes to appropriate overflow check
 // Unsigned: Match on the type of BinOp and call the correct function
-fn unsigned_overflow(binop: &BinOp, lvalue: &Expression, rvalue: &Expression) -> Expression {
+fn unsigned_overflow(binop: &BinOp, size: u8, lvalue: &Expression, rvalue: &Expression)
+                     -> Expression {
     match *binop {
         BinOp::Add => unsigned_add(lvalue, rvalue),
         BinOp::Sub => unsigned_sub(lvalue, rvalue),
         BinOp::Mul => unsigned_mul(lvalue, rvalue),
         BinOp::Div => unreachable!(),
         BinOp::Rem => unreachable!(),
-        BinOp::Shl => unimplemented!(),
-        BinOp::Shr => unimplemented!(),
+        BinOp::Shl => shift_overflow(size, rvalue),
+        BinOp::Shr => shift_overflow(size, rvalue),
         BinOp::BitOr => unimplemented!(),
         BinOp::BitAnd => unimplemented!(),
         BinOp::BitXor => unimplemented!(),
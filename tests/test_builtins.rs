@@ -0,0 +1,83 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// abs, min, max Tests
+// * * *
+
+// Should be valid
+#[condition(pre="x > -100 && x < 100", post="return == abs(x)")]
+fn valid_abs(x: i32) -> i32 {
+    if x < 0 { -x } else { x }
+}
+
+// Should be invalid
+#[condition(pre="x > -100 && x < 100", post="return == abs(x)")]
+fn invalid_abs(x: i32) -> i32 {
+    -x
+}
+
+// Should be valid
+#[condition(pre="true", post="return == max(a, b)")]
+fn valid_max(a: u32, b: u32) -> u32 {
+    if a > b { a } else { b }
+}
+
+// Should be invalid
+#[condition(pre="true", post="return == min(a, b)")]
+fn invalid_min(a: i64, b: i64) -> i64 {
+    if a > b { a } else { b }
+}
+
+// * * *
+// pow Tests
+// * * *
+
+// Should be valid
+#[condition(pre="x < 1000", post="return == pow(x, 2)")]
+fn valid_square(x: u32) -> u32 {
+    x * x
+}
+
+// Should be invalid
+#[condition(pre="x < 100", post="return == pow(x, 3)")]
+fn invalid_cube(x: u32) -> u32 {
+    x * x
+}
+
+// * * *
+// Bit Count Tests
+// * * *
+
+// Should be valid
+#[condition(pre="x < 16", post="count_ones(return) == count_ones(x) + 1")]
+fn valid_count_ones(x: u8) -> u8 {
+    x | 16
+}
+
+// Should be valid
+#[condition(pre="x != 0", post="trailing_zeros(return) == 0")]
+fn valid_trailing_zeros(x: u16) -> u16 {
+    x | 1
+}
+
+// Should be valid
+#[condition(pre="x < 128", post="leading_zeros(return) == 0")]
+fn valid_leading_zeros(x: u8) -> u8 {
+    x + 128
+}
+
+// Should be valid
+#[condition(pre="is_power_of_two(x) && x < 1024", post="is_power_of_two(return)")]
+fn valid_power_of_two(x: u64) -> u64 {
+    x * 2
+}
+
+// Should be invalid
+#[condition(pre="true", post="is_power_of_two(return)")]
+fn invalid_power_of_two(x: u64) -> u64 {
+    x | 1
+}
@@ -0,0 +1,73 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Specification Case Tests
+// * * *
+
+// Should be valid
+#[condition(pre="x < 0", post="return == 0")]
+#[condition(pre="x >= 0 && x < 1000", post="return == x * 2")]
+fn valid_cases(x: i32) -> i32 {
+    if x < 0 { 0 } else { x * 2 }
+}
+
+// The second case fails
+// Should be invalid
+#[condition(pre="x < 0", post="return == 0")]
+#[condition(pre="x >= 0 && x < 1000", post="return == x * 2")]
+fn invalid_cases(x: i32) -> i32 {
+    if x < 0 { 0 } else { x + 2 }
+}
+
+// A shared precondition and postcondition apply to every case
+// Should be valid
+#[requires="x < 1000"]
+#[ensures="return >= 0"]
+#[condition(pre="x < 0", post="return == 0")]
+#[condition(pre="x >= 0", post="return == x * 2")]
+fn valid_shared_clauses(x: i32) -> i32 {
+    if x < 0 { 0 } else { x * 2 }
+}
+
+// * * *
+// Completeness and Disjointness Tests
+// * * *
+
+// Should be valid
+#[rustproof(complete, disjoint)]
+#[requires="x < 1000"]
+#[condition(pre="x < 0", post="return == 0")]
+#[condition(pre="x >= 0", post="return == x * 2")]
+fn valid_complete_disjoint(x: i32) -> i32 {
+    if x < 0 { 0 } else { x * 2 }
+}
+
+// The cases do not cover x == 0
+// Should be invalid
+#[rustproof(complete)]
+#[condition(pre="x < 0", post="return == 0")]
+#[condition(pre="x > 0 && x < 1000", post="return == x * 2")]
+fn invalid_incomplete(x: i32) -> i32 {
+    if x < 0 { 0 } else { x * 2 }
+}
+
+// The cases overlap at x == 0
+// Should be invalid
+#[rustproof(disjoint)]
+#[condition(pre="x <= 0", post="return == 0")]
+#[condition(pre="x >= 0 && x < 1000", post="return == x * 2")]
+fn invalid_overlapping(x: i32) -> i32 {
+    if x < 0 { 0 } else { x * 2 }
+}
+
+// Overlapping cases are allowed without the disjoint check
+// Should be valid
+#[condition(pre="x <= 0", post="return == 0")]
+#[condition(pre="x >= 0 && x < 1000", post="return == x * 2")]
+fn valid_overlapping(x: i32) -> i32 {
+    if x < 0 { 0 } else { x * 2 }
+}
@@ -0,0 +1,89 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Truncation Tests
+// * * *
+
+// Narrowing keeps the low bits
+// Should be valid
+#[condition(pre="x == 300u32", post="return == 44u8")]
+fn valid_truncate_u32_to_u8(x: u32) -> u8 {
+    x as u8
+}
+
+// Should be invalid
+#[condition(pre="true", post="return as u32 == x")]
+fn invalid_truncate_u32_to_u8(x: u32) -> u8 {
+    x as u8
+}
+
+// Same width casts reinterpret the bits
+// Should be valid
+#[condition(pre="x == 255u8", post="return == -1i8")]
+fn valid_reinterpret_u8_as_i8(x: u8) -> i8 {
+    x as i8
+}
+
+// A truncated value may still overflow
+// Should be invalid
+#[condition(pre="true", post="true")]
+fn invalid_truncated_addition(x: u32) -> u8 {
+    x as u8 + 1
+}
+
+// * * *
+// Extension Tests
+// * * *
+
+// Widening a signed value extends its sign
+// Should be valid
+#[condition(pre="x < 0i8", post="return < 0i64")]
+fn valid_sign_extend_i8_to_i64(x: i8) -> i64 {
+    x as i64
+}
+
+// Should be invalid
+#[condition(pre="true", post="return >= 0i64")]
+fn invalid_sign_extend_i32_to_i64(x: i32) -> i64 {
+    x as i64
+}
+
+// The signedness of the source, not the target, decides the extension
+// Should be valid
+#[condition(pre="x == -1i8", post="return == 65535u16")]
+fn valid_sign_extend_i8_to_u16(x: i8) -> u16 {
+    x as u16
+}
+
+// Should be valid
+#[condition(pre="x == 255u8", post="return == 255i32")]
+fn valid_zero_extend_u8_to_i32(x: u8) -> i32 {
+    x as i32
+}
+
+// Widening before adding avoids overflow
+// Should be valid
+#[condition(pre="true", post="return == x as u16 + 1u16")]
+fn valid_widened_addition(x: u8) -> u16 {
+    x as u16 + 1
+}
+
+// * * *
+// Boolean Tests
+// * * *
+
+// Should be valid
+#[condition(pre="true", post="(b => return == 1u8) && (!b => return == 0u8)")]
+fn valid_bool_as_u8(b: bool) -> u8 {
+    b as u8
+}
+
+// Should be invalid
+#[condition(pre="true", post="return == 1u8")]
+fn invalid_bool_as_u8(b: bool) -> u8 {
+    b as u8
+}
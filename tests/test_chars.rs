@@ -0,0 +1,46 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Character Tests
+// * * *
+
+// A char argument holds a Unicode scalar value
+// Should be valid
+#[condition(pre="true", post="return <= 1114111u32 && (return < 55296u32 || return > 57343u32)")]
+fn valid_char_code(c: char) -> u32 {
+    c as u32
+}
+
+// Should be invalid
+#[condition(pre="true", post="return < 55296u32")]
+fn invalid_char_code(c: char) -> u32 {
+    c as u32
+}
+
+// Should be valid
+#[condition(pre="true", post="return == (c >= 'A' && c <= 'Z')")]
+fn valid_is_upper(c: char) -> bool {
+    c >= 'A' && c <= 'Z'
+}
+
+// Should be valid
+#[condition(pre="c == 'a'", post="return == 97u8")]
+fn valid_char_as_u8(c: char) -> u8 {
+    c as u8
+}
+
+// Should be valid
+#[condition(pre="true", post="return as u32 == b as u32")]
+fn valid_byte_as_char(b: u8) -> char {
+    b as char
+}
+
+// Should be invalid
+#[condition(pre="true", post="return == 'a'")]
+fn invalid_byte_as_char(b: u8) -> char {
+    b as char
+}
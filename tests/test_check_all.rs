@@ -0,0 +1,53 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof(check_all))]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Unannotated Function Tests
+// * * *
+
+// Can overflow
+// Should be invalid
+fn invalid_unchecked_add(x: u32, y: u32) -> u32 {
+    x + y
+}
+
+// Can divide by zero
+// Should be invalid
+fn invalid_unchecked_div(x: i32, y: i32) -> i32 {
+    x / y
+}
+
+// Can panic explicitly
+// Should be invalid
+fn invalid_unchecked_panic(x: u8) -> u8 {
+    if x > 200 {
+        panic!("too large");
+    }
+    x
+}
+
+// Cannot panic, so is not reported
+// Should be valid
+fn valid_unchecked_halve(x: u32) -> u32 {
+    x / 2
+}
+
+// Cannot panic, so is not reported
+// Should be valid
+fn valid_unchecked_guarded_add(x: u8, y: u8) -> u8 {
+    if x < 100 && y < 100 {
+        x + y
+    } else {
+        0
+    }
+}
+
+// Annotated functions are checked against their specification as before
+// Should be valid
+#[condition(pre="x < 100", post="return == x + 1")]
+fn valid_annotated_increment(x: u32) -> u32 {
+    x + 1
+}
@@ -0,0 +1,67 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Requires and Ensures Tests
+// * * *
+
+// Should be valid
+#[requires="x < 100"]
+#[requires="y < 100"]
+#[ensures="return >= x"]
+#[ensures="return >= y"]
+fn valid_add(x: u32, y: u32) -> u32 {
+    x + y
+}
+
+// Only the second postcondition fails
+// Should be invalid
+#[requires="x < 100"]
+#[requires="y < 100"]
+#[ensures="return >= x"]
+#[ensures="return > y"]
+fn invalid_add(x: u32, y: u32) -> u32 {
+    x + y
+}
+
+// A precondition is required for the addition not to overflow
+// Should be invalid
+#[requires="x < 100"]
+#[ensures="return >= x"]
+fn invalid_missing_requires(x: u32, y: u32) -> u32 {
+    x + y
+}
+
+// Without a postcondition, only the absence of overflow is checked
+// Should be valid
+#[requires="x < 100"]
+fn valid_no_ensures(x: u8) -> u8 {
+    x + 100
+}
+
+// Should be invalid
+#[requires="x < 200"]
+fn invalid_no_ensures(x: u8) -> u8 {
+    x + 100
+}
+
+// * * *
+// Mixed Attribute Tests
+// * * *
+
+// Should be valid
+#[condition(pre="x > 0", post="return < x")]
+#[ensures="return >= 0"]
+fn valid_mixed_attributes(x: i32) -> i32 {
+    x - 1
+}
+
+// Should be invalid
+#[condition(pre="x > 0", post="return < x")]
+#[ensures="return > 0"]
+fn invalid_mixed_attributes(x: i32) -> i32 {
+    x - 1
+}
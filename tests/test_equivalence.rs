@@ -0,0 +1,104 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Equivalence Tests
+// * * *
+
+fn abs_reference(x: i32) -> i32 {
+    if x < 0 {
+        -x
+    } else {
+        x
+    }
+}
+
+// Both overflow for i32::MIN
+// Should be valid
+#[equivalent_to(abs_reference)]
+fn valid_abs(x: i32) -> i32 {
+    let mask = x >> 31;
+    (x ^ mask) - mask
+}
+
+// Negative inputs are off by two
+// Should be invalid
+#[equivalent_to(abs_reference)]
+fn invalid_abs(x: i32) -> i32 {
+    let mask = x >> 31;
+    (x ^ mask) + mask
+}
+
+mod reference {
+    pub fn half(x: u32) -> u32 {
+        x / 2
+    }
+
+    pub fn signed_half(x: i32) -> i32 {
+        x / 2
+    }
+
+    pub fn increment(x: u32) -> u32 {
+        x + 1
+    }
+
+    pub fn swap(x: &mut u32, y: &mut u32) {
+        let t = *x;
+        *x = *y;
+        *y = t;
+    }
+}
+
+// Should be valid
+#[equivalent_to="reference::half"]
+fn valid_half(x: u32) -> u32 {
+    x >> 1
+}
+
+// Division rounds towards zero, and shifting towards negative infinity
+// Should be invalid
+#[equivalent_to="reference::signed_half"]
+fn invalid_signed_half(x: i32) -> i32 {
+    x >> 1
+}
+
+// The reference panics for u32::MAX
+// Should be invalid
+#[equivalent_to="reference::increment"]
+fn invalid_panic_behaviour(x: u32) -> u32 {
+    if x == 4294967295 {
+        0
+    } else {
+        x + 1
+    }
+}
+
+// The inputs are restricted by the preconditions
+// Should be valid
+#[condition(pre="x < 4294967295u32", post="return == x + 1u32")]
+#[equivalent_to="reference::increment"]
+fn valid_bounded_increment(x: u32) -> u32 {
+    if x == 4294967295 {
+        0
+    } else {
+        x + 1
+    }
+}
+
+// The values behind reference arguments are compared as well
+// Should be valid
+#[equivalent_to="reference::swap"]
+fn valid_xor_swap(x: &mut u32, y: &mut u32) {
+    *x ^= *y;
+    *y ^= *x;
+    *x ^= *y;
+}
+
+// Should be invalid
+#[equivalent_to="reference::swap"]
+fn invalid_swap(x: &mut u32, y: &mut u32) {
+    *x = *y;
+}
@@ -0,0 +1,109 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Floating-Point Tests
+// * * *
+
+// Should be valid
+#[condition(pre="!is_nan(a) && !is_nan(b)", post="return >= a && return >= b")]
+fn valid_max(a: f64, b: f64) -> f64 {
+    if a >= b { a } else { b }
+}
+
+// NaN is not greater than or equal to anything
+// Should be invalid
+#[condition(pre="true", post="return >= a && return >= b")]
+fn invalid_max_nan(a: f64, b: f64) -> f64 {
+    if a >= b { a } else { b }
+}
+
+// Should be valid
+#[condition(pre="true", post="return == (x < 1.5f32)")]
+fn valid_less_than_literal(x: f32) -> bool {
+    x < 1.5
+}
+
+// Should be valid
+#[condition(pre="true", post="return == is_nan(x)")]
+fn valid_is_nan(x: f64) -> bool {
+    x != x
+}
+
+// Should be invalid
+#[condition(pre="true", post="return")]
+fn invalid_reflexive_equality(x: f64) -> bool {
+    x == x
+}
+
+// Should be valid
+#[condition(pre="true", post="return == (x > f64::NEG_INFINITY && x < f64::INFINITY)")]
+fn valid_is_finite(x: f64) -> bool {
+    x.is_finite()
+}
+
+// Should be valid
+#[condition(pre="!is_nan(x)", post="return == -x && (x > 0.0f32 => return < 0.0f32)")]
+fn valid_negation(x: f32) -> f32 {
+    -x
+}
+
+// -0.0 and 0.0 are equal
+// Should be valid
+#[condition(pre="x == 0.0f32", post="return == 0.0f32")]
+fn valid_negative_zero(x: f32) -> f32 {
+    -x
+}
+
+// * * *
+// Floating-Point Arithmetic Tests
+// * * *
+
+// Should be valid
+#[condition(pre="x >= 0.0f64 && x <= 1.0f64", post="return >= 0.0f64 && return <= 2.0f64")]
+fn valid_bounded_sum(x: f64) -> f64 {
+    x + x
+}
+
+// The sum is rounded to the nearest f64
+// Should be invalid
+#[condition(pre="true", post="return == 0.3f64")]
+fn invalid_rounded_sum() -> f64 {
+    0.1 + 0.2
+}
+
+// Should be valid
+#[condition(pre="is_finite(a) && is_finite(b)", post="!is_nan(return)")]
+fn valid_product_not_nan(a: f64, b: f64) -> f64 {
+    a * b
+}
+
+// The sum of large finite values overflows to infinity
+// Should be invalid
+#[condition(pre="is_finite(x)", post="is_finite(return)")]
+fn invalid_finite_sum(x: f32) -> f32 {
+    x + x
+}
+
+// Dividing by zero does not panic
+// Should be valid
+#[condition(pre="x > 0.0f32", post="return == f32::INFINITY")]
+fn valid_divide_by_zero(x: f32) -> f32 {
+    x / 0.0
+}
+
+// 2^24 + 1 has no f32 and rounds to the even neighbour
+// Should be valid
+#[condition(pre="n == 16777217u32", post="return == 16777216.0f32")]
+fn valid_rounded_cast(n: u32) -> f32 {
+    n as f32
+}
+
+// Should be valid
+#[condition(pre="true", post="return == (x as f64)")]
+fn valid_widened_cast(x: f32) -> f64 {
+    x as f64
+}
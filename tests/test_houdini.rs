@@ -0,0 +1,57 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof(print_invariants))]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Template Invariant Inference Tests
+// * * *
+
+// The invariants i <= n and sum == old(x) + i are inferred
+// Should be valid
+#[condition(pre="x <= 1000u32 && n <= 1000u32", post="return == x + n")]
+fn valid_add(x: u32, n: u32) -> u32 {
+    let mut sum = x;
+    let mut i = 0;
+    while i < n {
+        sum += 1;
+        i += 1;
+    }
+    sum
+}
+
+// The invariant total >= 0 is inferred
+// Should be valid
+#[condition(pre="n >= 0i32", post="return >= 0i32 && return <= 10i32")]
+fn valid_drain(n: i32) -> i32 {
+    let mut total = n;
+    while total > 10 {
+        total -= 3;
+    }
+    total
+}
+
+// The invariant left >= 0 does not hold on entry, so it is not inferred
+// Should be invalid
+#[condition(pre="true", post="return >= 0i32")]
+fn invalid_drain(n: i32) -> i32 {
+    let mut left = n;
+    while left > 10 {
+        left -= 3;
+    }
+    left
+}
+
+// No candidate relates the result to n * n
+// Should be invalid
+#[condition(pre="n <= 100u32", post="return == n * n")]
+fn invalid_square(n: u32) -> u32 {
+    let mut i = 0;
+    let mut square = 0;
+    while i < n {
+        square += n;
+        i += 1;
+    }
+    square
+}
@@ -0,0 +1,54 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Type Inference Tests
+// * * *
+
+// Variable and literal types are inferred from the signature
+// Should be valid
+#[condition(pre="(x <= i32::MAX - 5) && (x >= i32::MIN + 5)", post="return == x + 5")]
+fn valid_inferred_add_five_i32(x: i32) -> i32 {
+    x+5
+}
+
+// Should be invalid
+#[condition(pre="x <= i32::MAX - 4", post="return == x + 5")]
+fn invalid_inferred_add_five_i32(x: i32) -> i32 {
+    x+5
+}
+
+// Untyped and typed operands may be mixed
+// Should be valid
+#[condition(pre="x: u64 <= u64::MAX - 5", post="return: u64 == x + 5u64")]
+fn valid_mixed_add_five_u64(x: u64) -> u64 {
+    x+5
+}
+
+// A literal on the left takes the type of the right operand
+// Should be valid
+#[condition(pre="10 > x", post="return == 10 - x")]
+fn valid_inferred_sub_u8(x: u8) -> u8 {
+    10-x
+}
+
+// Negative literals are inferred for signed types
+// Should be valid
+#[condition(pre="x > -100 && x < 100", post="return == x * -1")]
+fn valid_inferred_negate_i16(x: i16) -> i16 {
+    x * -1
+}
+
+// Boolean variables are inferred as well
+// Should be invalid
+#[condition(pre="y", post="return == x + 6")]
+fn invalid_inferred_conditional(x: i32, y: bool) -> i32 {
+    if y {
+        x + 5
+    } else {
+        x + 6
+    }
+}
@@ -0,0 +1,124 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Checked Method Tests
+// * * *
+
+// Should be valid
+#[condition(pre="true", post="return <= x")]
+fn valid_checked_add_or_zero(x: u32, y: u32) -> u32 {
+    match x.checked_add(y) {
+        Some(_) => x,
+        None => 0,
+    }
+}
+
+// Should be valid
+#[condition(pre="true", post="(x < 200u8 && y < 50u8) => return == x + y")]
+fn valid_checked_add_unwrap_or(x: u8, y: u8) -> u8 {
+    x.checked_add(y).unwrap_or(255)
+}
+
+// Should be invalid
+#[condition(pre="true", post="return != 0i32")]
+fn invalid_checked_div_none(x: i32, y: i32) -> i32 {
+    x.checked_div(y).unwrap_or(0)
+}
+
+// Should be valid
+#[condition(pre="true", post="return == (y == 0i32 || (x == -2147483647i32 - 1i32 && y == -1i32))")]
+fn valid_checked_div_is_none(x: i32, y: i32) -> bool {
+    x.checked_div(y).is_none()
+}
+
+// Should be valid
+#[condition(pre="x < 100u64", post="return == x * 3u64")]
+fn valid_checked_mul_unwrap(x: u64) -> u64 {
+    x.checked_mul(3).unwrap()
+}
+
+// Unwrapping None panics
+// Should be invalid
+#[condition(pre="true", post="true")]
+fn invalid_checked_mul_unwrap(x: u64) -> u64 {
+    x.checked_mul(3).unwrap()
+}
+
+// Should be valid
+#[condition(pre="true", post="return == (x != -128i8)")]
+fn valid_checked_neg_is_some(x: i8) -> bool {
+    x.checked_neg().is_some()
+}
+
+// * * *
+// Saturating Method Tests
+// * * *
+
+// Should be valid
+#[condition(pre="true", post="return <= x")]
+fn valid_saturating_sub(x: u16, y: u16) -> u16 {
+    x.saturating_sub(y)
+}
+
+// Should be valid
+#[condition(pre="x > 1000i32 && y > 2147483000i32", post="return == 2147483647i32")]
+fn valid_saturating_add_max(x: i32, y: i32) -> i32 {
+    x.saturating_add(y)
+}
+
+// Should be valid
+#[condition(pre="x < 0i16 && y > 20000i16", post="return == -32767i16 - 1i16")]
+fn valid_saturating_mul_min(x: i16, y: i16) -> i16 {
+    x.saturating_mul(y)
+}
+
+// Should be invalid
+#[condition(pre="true", post="return >= x")]
+fn invalid_saturating_add(x: i64, y: i64) -> i64 {
+    x.saturating_add(y)
+}
+
+// * * *
+// Overflowing Method Tests
+// * * *
+
+// Should be valid
+#[condition(pre="true", post="return == (x > 127u8)")]
+fn valid_overflowing_mul(x: u8) -> bool {
+    let (_, overflowed) = x.overflowing_mul(2);
+    overflowed
+}
+
+// Should be valid
+#[condition(pre="x == 255u8", post="return == 254u8")]
+fn valid_overflowing_mul_value(x: u8) -> u8 {
+    let (value, _) = x.overflowing_mul(2);
+    value
+}
+
+// * * *
+// Wrapping Method and abs Tests
+// * * *
+
+// Should be valid
+#[condition(pre="y == 33u32", post="return == x << 1u32")]
+fn valid_wrapping_shl(x: u32, y: u32) -> u32 {
+    x.wrapping_shl(y)
+}
+
+// abs(i32::MIN) overflows
+// Should be invalid
+#[condition(pre="true", post="return >= 0i32")]
+fn invalid_abs(x: i32) -> i32 {
+    x.abs()
+}
+
+// Should be valid
+#[condition(pre="x > -1000i32", post="return >= 0i32 && (return == x || return == -x)")]
+fn valid_abs(x: i32) -> i32 {
+    x.abs()
+}
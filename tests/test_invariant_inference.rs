@@ -0,0 +1,69 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof(print_invariants))]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Loop Invariant Inference Tests
+// * * *
+
+// The invariant i <= n is inferred
+// Should be valid
+#[condition(pre="true", post="return == n")]
+fn valid_count_to(n: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    i
+}
+
+// The invariant i <= 10 is inferred
+// Should be valid
+#[condition(pre="true", post="return == 10u32")]
+fn valid_count_to_constant() -> u32 {
+    let mut i = 0;
+    while i < 10 {
+        i += 1;
+    }
+    i
+}
+
+// Should be valid
+#[condition(pre="true", post="return == 0u32")]
+fn valid_count_down(n: u32) -> u32 {
+    let mut i = n;
+    while i > 0 {
+        i -= 1;
+    }
+    i
+}
+
+// Nothing is inferred about the sum, which may overflow as far as the proof knows
+// Should be invalid
+#[condition(pre="n <= 100u32", post="return == 2u32 * n")]
+fn invalid_sum(n: u32) -> u32 {
+    let mut i = 0;
+    let mut sum = 0;
+    while i < n {
+        sum += 2;
+        i += 1;
+    }
+    sum
+}
+
+// The inferred invariants of nested loops
+// Should be valid
+#[condition(pre="true", post="return == n")]
+fn valid_nested(n: u32, m: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        let mut j = 0;
+        while j < m {
+            j += 1;
+        }
+        i += 1;
+    }
+    i
+}
@@ -0,0 +1,50 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// If-Then-Else Tests
+// * * *
+
+// Should be valid
+#[condition(pre="x > -100 && x < 100", post="return == if x > 0 then x else -x")]
+fn valid_abs(x: i32) -> i32 {
+    if x > 0 { x } else { -x }
+}
+
+// Should be invalid
+#[condition(pre="x > -100 && x < 100", post="return == if x > 0 then x else -x")]
+fn invalid_abs(x: i32) -> i32 {
+    x
+}
+
+// A conditional at the top level of a condition
+// Should be valid
+#[condition(pre="true", post="if a then return == 1u8 else return == 0u8")]
+fn valid_top_level_conditional(a: bool) -> u8 {
+    if a { 1 } else { 0 }
+}
+
+// * * *
+// Let Tests
+// * * *
+
+// Should be valid
+#[condition(pre="x < 1000", post="let d = x * 2 in return == d + 1")]
+fn valid_let(x: u32) -> u32 {
+    x + x + 1
+}
+
+// Should be invalid
+#[condition(pre="x < 1000", post="let d = x * 2 in return == d")]
+fn invalid_let(x: u32) -> u32 {
+    x + 1
+}
+
+// Should be valid
+#[condition(pre="x < 10", post="let y: u64 = 3 in let z = y + x in return == z")]
+fn valid_nested_let(x: u64) -> u64 {
+    x + 3
+}
@@ -0,0 +1,92 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// K-Induction Tests
+// * * *
+
+// An inductive invariant is proved by 1-induction
+// Should be valid
+#[condition(pre="true", post="return == n")]
+#[loop_condition(invariant="i <= n")]
+#[rustproof(k_induction="3")]
+fn valid_count_to(n: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    i
+}
+
+// x is bounded because it takes the value y had one iteration earlier
+// Should be valid
+#[condition(pre="true", post="return <= 10u32")]
+#[loop_condition(invariant="x <= 10u32")]
+#[rustproof(k_induction="2")]
+fn valid_delayed(n: u32) -> u32 {
+    let mut x = 0;
+    let mut y = 0;
+    let mut i = 0;
+    while i < n {
+        x = y;
+        y = 5;
+        i += 1;
+    }
+    x
+}
+
+// The invariant holds, but is not 1-inductive
+// Should be invalid
+#[condition(pre="true", post="return <= 10u32")]
+#[loop_condition(invariant="x <= 10u32")]
+#[rustproof(k_induction="1")]
+fn invalid_delayed(n: u32) -> u32 {
+    let mut x = 0;
+    let mut y = 0;
+    let mut i = 0;
+    while i < n {
+        x = y;
+        y = 5;
+        i += 1;
+    }
+    x
+}
+
+// x takes the value z had two iterations earlier
+// Should be valid
+#[condition(pre="true", post="return <= 10u32")]
+#[loop_condition(invariant="x <= 10u32")]
+#[rustproof(k_induction="4")]
+fn valid_chain(n: u32) -> u32 {
+    let mut x = 0;
+    let mut y = 0;
+    let mut z = 0;
+    let mut i = 0;
+    while i < n {
+        x = y;
+        y = z;
+        z = 5;
+        i += 1;
+    }
+    x
+}
+
+// The invariant does not hold after the first iteration
+// Should be invalid
+#[condition(pre="true", post="return <= 10u32")]
+#[loop_condition(invariant="x <= 10u32")]
+#[rustproof(k_induction="4")]
+fn invalid_base_case(n: u32) -> u32 {
+    let mut x = 0;
+    let mut y = 20;
+    let mut i = 0;
+    while i < n {
+        x = y;
+        y = 5;
+        i += 1;
+    }
+    x
+}
@@ -0,0 +1,100 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Loop Tests
+// * * *
+
+// Should be valid
+#[condition(pre="true", post="return == n")]
+#[loop_condition(invariant="i <= n", variant="n - i")]
+fn valid_count_to(n: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    i
+}
+
+// The invariant is not preserved by the last iteration
+// Should be invalid
+#[condition(pre="true", post="return == n")]
+#[loop_condition(invariant="i < n")]
+fn invalid_invariant(n: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    i
+}
+
+// All that is known after the loop is the invariant and the negated loop condition
+// Should be invalid
+#[condition(pre="true", post="return == n")]
+#[loop_condition(invariant="true")]
+fn invalid_weak_invariant(n: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    i
+}
+
+// The variant does not decrease
+// Should be invalid
+#[condition(pre="true", post="return == n")]
+#[loop_condition(invariant="i <= n", variant="n")]
+fn invalid_variant(n: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    i
+}
+
+// The variant may become negative
+// Should be invalid
+#[condition(pre="true", post="true")]
+#[loop_condition(invariant="true", variant="y")]
+fn invalid_negative_variant(x: i32) -> i32 {
+    let mut y = x;
+    while y > -10 {
+        y -= 1;
+    }
+    y
+}
+
+// Should be valid
+#[condition(pre="n <= 1000u32", post="return <= 1000000u32")]
+#[loop_condition(invariant="i <= n && sum <= i * 1000u32", variant="n - i")]
+fn valid_sum(n: u32) -> u32 {
+    let mut i = 0;
+    let mut sum = 0;
+    while i < n {
+        sum += i;
+        i += 1;
+    }
+    sum
+}
+
+// Loops are described in the order they are written
+// Should be valid
+#[condition(pre="n <= 100u32", post="return == n * n")]
+#[loop_condition(invariant="i <= n && count == i * n", variant="n - i")]
+#[loop_condition(invariant="j <= n && count == i * n + j", variant="n - j")]
+fn valid_nested(n: u32) -> u32 {
+    let mut count = 0;
+    let mut i = 0;
+    while i < n {
+        let mut j = 0;
+        while j < n {
+            count += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    count
+}
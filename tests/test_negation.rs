@@ -0,0 +1,46 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Negation Overflow Tests
+// * * *
+
+// -i8::MIN overflows
+// Should be invalid
+#[condition(pre="true", post="return == -x")]
+fn invalid_negate_i8(x: i8) -> i8 {
+    -x
+}
+
+// Should be valid
+#[condition(pre="x >= -127i8", post="return == -x")]
+fn valid_negate_i8(x: i8) -> i8 {
+    -x
+}
+
+// Should be invalid
+#[condition(pre="x <= 0i16", post="return >= 0i16")]
+fn invalid_negate_i16(x: i16) -> i16 {
+    -x
+}
+
+// Should be valid
+#[condition(pre="x <= 0i32 && x > -100000i32", post="return >= 0i32")]
+fn valid_negate_i32(x: i32) -> i32 {
+    -x
+}
+
+// Should be invalid
+#[condition(pre="x < 0i64", post="return > 0i64")]
+fn invalid_negate_i64(x: i64) -> i64 {
+    -x
+}
+
+// Should be valid
+#[condition(pre="x < 0i64 && x > -100i64", post="return > 0i64")]
+fn valid_negate_i64(x: i64) -> i64 {
+    -x
+}
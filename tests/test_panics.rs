@@ -0,0 +1,79 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Explicit Panic Tests
+// * * *
+
+// Should be valid
+#[requires="x < 1000"]
+#[panics_if="x == 0"]
+#[ensures="return == 1000 / x"]
+fn valid_explicit_panic(x: u32) -> u32 {
+    if x == 0 {
+        panic!("no one to share with");
+    }
+    1000 / x
+}
+
+// The function also panics when x == 1
+// Should be invalid
+#[requires="x < 1000"]
+#[panics_if="x == 0"]
+fn invalid_panics_more(x: u32) -> u32 {
+    if x <= 1 {
+        panic!("too few");
+    }
+    x
+}
+
+// The function never panics
+// Should be invalid
+#[panics_if="x == 0"]
+fn invalid_panics_less(x: u32) -> u32 {
+    x
+}
+
+// * * *
+// Runtime Check Tests
+// * * *
+
+// A failed overflow check is a panic
+// Should be valid
+#[panics_if="x > 155"]
+#[ensures="return == x + 100"]
+fn valid_overflow_panic(x: u8) -> u8 {
+    x + 100
+}
+
+// A failed division check is a panic
+// Should be valid
+#[panics_if="y == 0"]
+#[ensures="return == x / y"]
+fn valid_division_panic(x: u32, y: u32) -> u32 {
+    x / y
+}
+
+// Should be invalid
+#[panics_if="y == 0"]
+fn invalid_division_panic(x: i32, y: i32) -> i32 {
+    x / y
+}
+
+// * * *
+// Case Tests
+// * * *
+
+// Should be valid
+#[condition(pre="x < 10", panics_if="x == 0", post="return == 10 / x")]
+#[condition(pre="x >= 10", post="return == 0")]
+fn valid_case_panic(x: u32) -> u32 {
+    if x >= 10 {
+        0
+    } else {
+        10 / x
+    }
+}
@@ -0,0 +1,68 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Pointer-Sized Integer Tests
+// * * *
+
+// Should be valid
+#[condition(pre="i < len", post="return == i + 1usize")]
+fn valid_next_index(i: usize, len: usize) -> usize {
+    i + 1
+}
+
+// Should be invalid
+#[condition(pre="true", post="return == i + 1usize")]
+fn invalid_next_index(i: usize) -> usize {
+    i + 1
+}
+
+// Should be valid
+#[condition(pre="x > isize::MIN", post="return >= 0isize")]
+fn valid_abs_isize(x: isize) -> isize {
+    if x < 0 { -x } else { x }
+}
+
+// Should be invalid
+#[condition(pre="true", post="return >= 0isize")]
+fn invalid_abs_isize(x: isize) -> isize {
+    if x < 0 { -x } else { x }
+}
+
+// * * *
+// Pointer Width Tests
+// * * *
+
+// A usize holds any u32 on both 32- and 64-bit targets
+// Should be valid
+#[rustproof(pointer_width="32, 64")]
+#[condition(pre="true", post="return as u32 == x")]
+fn valid_u32_as_usize(x: u32) -> usize {
+    x as usize
+}
+
+// A usize holds any u64 on a 64-bit target only
+// Should be valid
+#[rustproof(pointer_width="64")]
+#[condition(pre="true", post="return as u64 == x")]
+fn valid_u64_as_usize_64(x: u64) -> usize {
+    x as usize
+}
+
+// Should be invalid
+#[rustproof(pointer_width="32")]
+#[condition(pre="true", post="return as u64 == x")]
+fn invalid_u64_as_usize_32(x: u64) -> usize {
+    x as usize
+}
+
+// usize::MAX is the maximum of the chosen width
+// Should be valid
+#[rustproof(pointer_width="16")]
+#[condition(pre="true", post="return <= 65535usize")]
+fn valid_usize_16(x: usize) -> usize {
+    x
+}
@@ -0,0 +1,62 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+#[predicate]
+fn in_range(x: u32, lo: u32, hi: u32) -> bool {
+    lo <= x && x < hi
+}
+
+#[predicate]
+fn is_even(x: i32) -> bool {
+    x % 2 == 0
+}
+
+// * * *
+// Predicate Tests
+// * * *
+
+// Should be valid
+#[condition(pre="in_range(x, 0, 100)", post="in_range(return, 1, 101)")]
+fn valid_succ(x: u32) -> u32 {
+    x + 1
+}
+
+// Should be invalid
+#[condition(pre="in_range(x, 0, 100)", post="in_range(return, 0, 100)")]
+fn invalid_succ(x: u32) -> u32 {
+    x + 1
+}
+
+// Arguments naming another parameter are substituted correctly
+// Should be valid
+#[condition(pre="in_range(lo, 0, hi) && hi < 1000", post="in_range(return, lo, hi + 1)")]
+fn valid_swapped_names(lo: u32, hi: u32) -> u32 {
+    hi
+}
+
+// Should be valid
+#[condition(pre="x > -1000 && x < 1000", post="is_even(return)")]
+fn valid_double(x: i32) -> i32 {
+    x * 2
+}
+
+// Should be invalid
+#[condition(pre="x > -1000 && x < 1000", post="is_even(return)")]
+fn invalid_double(x: i32) -> i32 {
+    x * 2 + 1
+}
+
+// A predicate used after the function that calls it is defined
+// Should be valid
+#[condition(pre="true", post="is_small(return)")]
+fn valid_defined_later(x: u8) -> u8 {
+    x / 2
+}
+
+#[predicate]
+fn is_small(x: u8) -> bool {
+    x < 128
+}
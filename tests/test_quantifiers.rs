@@ -0,0 +1,81 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Universal Quantifier Tests
+// * * *
+
+// Should be valid
+#[condition(pre="x < 100", post="forall i: u32 in 0..x, i < return")]
+fn valid_forall_below(x: u32) -> u32 {
+    x + 1
+}
+
+// Should be invalid
+#[condition(pre="x < 100", post="forall i: u32 in 0..x, i + 1 < return")]
+fn invalid_forall_below(x: u32) -> u32 {
+    x
+}
+
+// An unbounded universal over the whole type
+// Should be valid
+#[condition(pre="true", post="forall i: u8, (i <= return) || (i > return)")]
+fn valid_forall_unbounded(x: u8) -> u8 {
+    x
+}
+
+// A constant-range universal in the precondition is expanded
+// Should be valid
+#[condition(pre="forall i: u8 in 0..4, x != i", post="return >= 3")]
+fn valid_forall_precondition(x: u8) -> u8 {
+    x - 1
+}
+
+// * * *
+// Existential Quantifier Tests
+// * * *
+
+// Should be valid
+#[condition(pre="exists k: i32 in 0..10, x == k * 2", post="return % 2 == 0")]
+fn valid_exists_even(x: i32) -> i32 {
+    x
+}
+
+// Should be valid
+#[condition(pre="x <= 10", post="exists k: u32 in 0..11, return == k * k")]
+fn valid_exists_square(x: u32) -> u32 {
+    x * x
+}
+
+// Should be invalid
+#[condition(pre="x <= 10", post="exists k: u32 in 0..11, return == k * k")]
+fn invalid_exists_square(x: u32) -> u32 {
+    x * x + 1
+}
+
+// * * *
+// Quantifiers Left to the Solver
+// * * *
+
+// The range of the assumed quantifier depends on an argument
+// Should be valid
+#[condition(pre="y < x && (forall i: u32 in 0..x, i != 7)", post="return != 7")]
+fn valid_forall_argument_range(x: u32, y: u32) -> u32 {
+    y
+}
+
+// Should be valid
+#[condition(pre="x > 0", post="exists k: u32, return == k + 1")]
+fn valid_exists_unbounded(x: u32) -> u32 {
+    x
+}
+
+// The range is too wide to expand
+// Should be invalid
+#[condition(pre="x < 1000", post="exists k: u32 in 0..1000, return == k * 2")]
+fn invalid_exists_wide_range(x: u32) -> u32 {
+    x
+}
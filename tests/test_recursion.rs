@@ -0,0 +1,89 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Recursion Tests
+// * * *
+
+// Should be valid
+#[condition(pre="true", post="(b > 0u32 => return <= b) && (b == 0u32 => return == a)")]
+#[decreases="b"]
+fn valid_gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        valid_gcd(b, a % b)
+    }
+}
+
+// a % b is not less than a when a < b
+// Should be invalid
+#[condition(pre="true", post="(b > 0u32 => return <= b) && (b == 0u32 => return == a)")]
+#[decreases="a"]
+fn invalid_gcd_measure(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        invalid_gcd_measure(b, a % b)
+    }
+}
+
+// Should be valid
+#[condition(pre="true", post="return == 0u32")]
+#[decreases="n"]
+fn valid_count_down(n: u32) -> u32 {
+    if n == 0 {
+        0
+    } else {
+        valid_count_down(n - 1)
+    }
+}
+
+// Should be valid
+#[condition(pre="n >= 0i32", post="return == 0i32")]
+#[decreases="n"]
+fn valid_signed_count_down(n: i32) -> i32 {
+    if n <= 0 {
+        0
+    } else {
+        valid_signed_count_down(n - 1)
+    }
+}
+
+// The measure does not decrease
+// Should be invalid
+#[condition(pre="true", post="return == 0u32")]
+#[decreases="n"]
+fn invalid_no_progress(n: u32) -> u32 {
+    if n == 0 {
+        0
+    } else {
+        invalid_no_progress(n)
+    }
+}
+
+// The recursive call does not satisfy the precondition
+// Should be invalid
+#[condition(pre="n < 100u32", post="true")]
+fn invalid_call_precondition(n: u32) -> u32 {
+    if n == 0 {
+        0
+    } else {
+        invalid_call_precondition(n + 100)
+    }
+}
+
+// Only the postcondition is known of the result of a recursive call
+// Should be valid
+#[condition(pre="true", post="return <= n")]
+#[decreases="n"]
+fn valid_bounded(n: u32) -> u32 {
+    if n == 0 {
+        0
+    } else {
+        valid_bounded(n - 1) + 1
+    }
+}
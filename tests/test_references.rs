@@ -0,0 +1,62 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Mutable Reference Tests
+// * * *
+
+// Should be valid
+#[condition(pre="*x < u32::MAX", post="*x == old(*x) + 1")]
+fn valid_incr(x: &mut u32) {
+    *x += 1;
+}
+
+// Should be invalid (overflow when *x == u32::MAX)
+#[condition(pre="true", post="*x == old(*x) + 1")]
+fn invalid_incr_overflow(x: &mut u32) {
+    *x += 1;
+}
+
+// Should be invalid
+#[condition(pre="*x < u32::MAX", post="*x == old(*x)")]
+fn invalid_incr_unchanged(x: &mut u32) {
+    *x += 1;
+}
+
+// Should be valid
+#[condition(pre="true", post="(*a == old(*b)) && (*b == old(*a))")]
+fn valid_swap(a: &mut i32, b: &mut i32) {
+    let t = *a;
+    *a = *b;
+    *b = t;
+}
+
+// Should be valid
+#[condition(pre="(y < i64::MAX - 5) && (y > i64::MIN + 5)", post="return == old(y) + 5")]
+fn valid_reborrow(y: i64) -> i64 {
+    let mut z = y;
+    {
+        let r = &mut z;
+        *r += 5;
+    }
+    z
+}
+
+// * * *
+// Shared Reference Tests
+// * * *
+
+// Should be valid
+#[condition(pre="*x <= 100", post="return == *x * 2")]
+fn valid_double(x: &u8) -> u8 {
+    *x * 2
+}
+
+// Should be invalid
+#[condition(pre="true", post="return == *x * 2")]
+fn invalid_double(x: &u8) -> u8 {
+    *x * 2
+}
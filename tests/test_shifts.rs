@@ -0,0 +1,68 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Shift Amount Tests
+// * * *
+
+// The shift amount may be 32 or more
+// Should be invalid
+#[condition(pre="true", post="true")]
+fn invalid_unchecked_shl_u32(x: u32, y: u32) -> u32 {
+    x << y
+}
+
+// Should be valid
+#[condition(pre="y < 32u32", post="true")]
+fn valid_checked_shl_u32(x: u32, y: u32) -> u32 {
+    x << y
+}
+
+// The shift amount may be negative
+// Should be invalid
+#[condition(pre="y < 8i32", post="true")]
+fn invalid_negative_shr_i8(x: i8, y: i32) -> i8 {
+    x >> y
+}
+
+// Should be valid
+#[condition(pre="y >= 0i32 && y < 8i32", post="return <= x || x < 0i8")]
+fn valid_shr_i8(x: i8, y: i32) -> i8 {
+    x >> y
+}
+
+// A u8 shift amount is always less than 64
+// Should be valid
+#[condition(pre="true", post="true")]
+fn valid_shl_u64_by_u8(x: u64, y: u8) -> u64 {
+    x << y
+}
+
+// The shift amount may be 64 or more
+// Should be invalid
+#[condition(pre="y < 100u8", post="true")]
+fn invalid_shl_u64_by_u8(x: u64, y: u8) -> u64 {
+    x << y
+}
+
+// Mixed-width shifts are encoded correctly
+// Should be valid
+#[condition(pre="y < 16u64", post="return == x >> y")]
+fn valid_shr_u16_by_u64(x: u16, y: u64) -> u16 {
+    x >> y
+}
+
+// Should be valid
+#[condition(pre="y == 4u8 && x < 1000u64", post="return == x * 16u64")]
+fn valid_shl_is_multiplication(x: u64, y: u8) -> u64 {
+    x << y
+}
+
+// Should be invalid
+#[condition(pre="y == 4u8 && x < 1000u64", post="return == x * 8u64")]
+fn invalid_shl_is_multiplication(x: u64, y: u8) -> u64 {
+    x << y
+}
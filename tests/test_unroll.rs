@@ -0,0 +1,78 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Loop Unrolling Tests
+// * * *
+
+// Should be valid
+#[condition(pre="n <= 5u32", post="return == n")]
+#[rustproof(unroll="8")]
+fn valid_count_to(n: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    i
+}
+
+// The loop may run more than 8 times
+// Should be invalid
+#[condition(pre="true", post="return == n")]
+#[rustproof(unroll="8")]
+fn invalid_unwinding(n: u32) -> u32 {
+    let mut i = 0;
+    while i < n {
+        i += 1;
+    }
+    i
+}
+
+// The loop runs exactly as many times as it is unrolled
+// Should be valid
+#[condition(pre="true", post="return == 4u8")]
+#[rustproof(unroll="4")]
+fn valid_exact_bound() -> u8 {
+    let mut i = 0;
+    loop {
+        if i == 4 {
+            break;
+        }
+        i += 1;
+    }
+    i
+}
+
+// x overflows on the sixth iteration
+// Should be invalid
+#[condition(pre="n <= 10u8", post="true")]
+#[rustproof(unroll="10")]
+fn invalid_overflow(n: u8) -> u8 {
+    let mut x: u8 = 250;
+    let mut i = 0;
+    while i < n {
+        x += 1;
+        i += 1;
+    }
+    x
+}
+
+// Should be valid
+#[condition(pre="n <= 3u32 && m <= 3u32", post="return == n * m")]
+#[rustproof(unroll="3")]
+fn valid_nested(n: u32, m: u32) -> u32 {
+    let mut count = 0;
+    let mut i = 0;
+    while i < n {
+        let mut j = 0;
+        while j < m {
+            count += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    count
+}
@@ -0,0 +1,71 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof)]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+use std::num::Wrapping;
+fn main() { }
+
+// * * *
+// Wrapping Arithmetic Tests
+// * * *
+
+// Overflow panics by default
+// Should be invalid
+#[condition(pre="true", post="true")]
+fn invalid_checked_add(x: u8, y: u8) -> u8 {
+    x + y
+}
+
+// Should be valid
+#[rustproof(arith="wrapping")]
+#[condition(pre="x == 255u8 && y == 1u8", post="return == 0u8")]
+fn valid_wrapping_add(x: u8, y: u8) -> u8 {
+    x + y
+}
+
+// Should be valid
+#[rustproof(arith="wrapping")]
+#[condition(pre="x == -128i8", post="return == -128i8")]
+fn valid_wrapping_negate(x: i8) -> i8 {
+    -x
+}
+
+// Shift amounts are masked to the width
+// Should be valid
+#[rustproof(arith="wrapping")]
+#[condition(pre="y == 9u32", post="return == x << 1u32")]
+fn valid_wrapping_shl(x: u8, y: u32) -> u8 {
+    x << y
+}
+
+// Division by zero still panics
+// Should be invalid
+#[rustproof(arith="wrapping")]
+#[condition(pre="true", post="true")]
+fn invalid_wrapping_div(x: u32, y: u32) -> u32 {
+    x / y
+}
+
+// Should be valid
+#[condition(pre="true", post="return == x - y")]
+fn valid_wrapping_sub_method(x: u16, y: u16) -> u16 {
+    x.wrapping_sub(y)
+}
+
+// Should be invalid
+#[condition(pre="true", post="return >= x")]
+fn invalid_wrapping_mul_method(x: u16, y: u16) -> u16 {
+    x.wrapping_mul(y)
+}
+
+// Should be valid
+#[condition(pre="x == 4294967295u32", post="return == 1u32")]
+fn valid_wrapping_type(x: Wrapping<u32>) -> Wrapping<u32> {
+    x + x + Wrapping(3)
+}
+
+// Should be invalid
+#[condition(pre="true", post="return > x")]
+fn invalid_wrapping_type(x: Wrapping<u32>) -> Wrapping<u32> {
+    x + Wrapping(1)
+}
@@ -0,0 +1,23 @@
+#![feature(plugin, custom_attribute)]
+#![plugin(rustproof(arith="wrapping"))]
+#![allow(dead_code)]
+#![allow(unused_attributes)]
+fn main() { }
+
+// * * *
+// Crate-level Wrapping Arithmetic Tests
+// * * *
+
+// Should be valid
+#[condition(pre="true", post="return == x * y + 1u64")]
+fn valid_crate_wrapping(x: u64, y: u64) -> u64 {
+    x * y + 1
+}
+
+// A function may still ask for checked arithmetic
+// Should be invalid
+#[rustproof(arith="checked")]
+#[condition(pre="true", post="return == x * y + 1u64")]
+fn invalid_function_checked(x: u64, y: u64) -> u64 {
+    x * y + 1
+}